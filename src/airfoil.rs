@@ -0,0 +1,528 @@
+use kurbo::{BezPath, ParamCurve, ParamCurveArclen, PathEl, PathSeg, Point};
+use std::fmt;
+use std::io::BufRead;
+
+/// Reads airfoil coordinates in either Selig or Lednicer format.
+///
+/// The format is detected from the first row of numbers: Lednicer files
+/// start with the number of upper and lower surface points, which must
+/// add up to the number of coordinates that follow. A count that doesn't
+/// is an error, rather than read as a Selig file.
+///
+/// The returned points are always in Selig order: from the trailing edge,
+/// over the upper surface to the leading edge and back along the lower surface.
+pub fn read_coordinates<R: BufRead>(reader: R) -> Vec<Point> {
+    let mut rows: Vec<Point> = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.unwrap();
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let numbers: Result<Vec<f64>, _> = line
+            .split(&[' ', '\t', ','][..])
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<f64>())
+            .collect();
+
+        match numbers {
+            Ok(numbers) if numbers.len() == 2 => rows.push(Point::new(numbers[0], numbers[1])),
+            // Anything before the first row of coordinates is the header.
+            _ if rows.is_empty() => continue,
+            Ok(numbers) => panic!(
+                "expected 2 decimal datapoints per line, got {} on line {}",
+                numbers.len(),
+                i + 1
+            ),
+            Err(_) => panic!("invalid number on line {}: {:?}", i + 1, line),
+        }
+    }
+
+    if rows.is_empty() {
+        panic!("no airfoil coordinates found");
+    }
+
+    let (n_upper, n_lower) = (rows[0].x, rows[0].y);
+    let is_count = |n: f64| n >= 2. && n.fract() == 0.;
+    if !is_count(n_upper) || !is_count(n_lower) {
+        return rows;
+    }
+    let (n_upper, n_lower) = (n_upper as usize, n_lower as usize);
+    if rows.len() - 1 != n_upper + n_lower {
+        panic!(
+            "Lednicer header gives {} upper and {} lower surface points, but {} follow",
+            n_upper,
+            n_lower,
+            rows.len() - 1
+        );
+    }
+    lednicer(&rows[1..], n_upper)
+}
+
+/// Rebuilds a Selig-ordered contour from Lednicer rows, which list the upper
+/// and then the lower surface, each running from the leading edge to the
+/// trailing edge.
+fn lednicer(rows: &[Point], n_upper: usize) -> Vec<Point> {
+    let upper = rows[..n_upper].iter().copied();
+    let mut lower = rows[n_upper..].iter().copied().peekable();
+
+    let mut points: Vec<Point> = upper.rev().collect();
+    // Both surfaces usually start at the leading edge: only keep it once.
+    if lower.peek() == points.last() {
+        lower.next();
+    }
+    points.extend(lower);
+    points
+}
+
+/// Builds a polyline path through the given points, skipping repeated points.
+pub fn path_from_points(points: &[Point]) -> BezPath {
+    let mut path = BezPath::new();
+    let mut last: Option<Point> = None;
+    for &p in points {
+        match last {
+            None => path.push(PathEl::MoveTo(p)),
+            Some(l) if l == p => continue,
+            Some(_) => path.push(PathEl::LineTo(p)),
+        }
+        last = Some(p);
+    }
+    path
+}
+
+/// Returns `n + 1` chord positions from 0 to 1, clustered towards both ends.
+pub fn cosine_spacing(n: usize) -> Vec<f64> {
+    (0..=n)
+        .map(|i| 0.5 * (1. - (std::f64::consts::PI * i as f64 / n as f64).cos()))
+        .collect()
+}
+
+/// Distribution of points along each surface when repaneling.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Spacing {
+    /// Clustered towards both the leading and trailing edges
+    Cosine,
+    /// Clustered towards the leading edge only
+    HalfCosine,
+}
+
+/// Resamples a contour to `n` points per surface, distributed by arc length.
+///
+/// The contour is split at the leading edge, taken as the vertex farthest
+/// from the trailing edge, and both surfaces are resampled with the same
+/// spacing. The returned points are in Selig order.
+pub fn repanel(path: &BezPath, n: usize, spacing: Spacing) -> Vec<Point> {
+    const ACCURACY: f64 = 1e-9;
+    let n = n.max(2);
+
+    let segs: Vec<PathSeg> = path.segments().collect();
+    let mut cumulative = vec![0.];
+    for seg in &segs {
+        cumulative.push(cumulative.last().unwrap() + seg.arclen(ACCURACY));
+    }
+    let total = *cumulative.last().unwrap();
+
+    let (start, end) = (segs[0].eval(0.), segs.last().unwrap().eval(1.));
+    let te = start.midpoint(end);
+    let le = (0..segs.len())
+        .max_by(|&a, &b| {
+            let (a, b) = (segs[a].eval(1.), segs[b].eval(1.));
+            a.distance_squared(te).total_cmp(&b.distance_squared(te))
+        })
+        .unwrap();
+    let upper_len = cumulative[le + 1];
+
+    let at = |s: f64| {
+        let i = cumulative.partition_point(|&c| c <= s).clamp(1, segs.len()) - 1;
+        let t = segs[i].inv_arclen(s - cumulative[i], ACCURACY);
+        segs[i].eval(t.clamp(0., 1.))
+    };
+
+    // Fraction of each surface's length, from the leading edge.
+    let fractions: Vec<f64> = match spacing {
+        Spacing::Cosine => cosine_spacing(n),
+        Spacing::HalfCosine => (0..=n)
+            .map(|i| 1. - (std::f64::consts::FRAC_PI_2 * i as f64 / n as f64).cos())
+            .collect(),
+    };
+
+    let upper = fractions.iter().rev().map(|f| at(upper_len * (1. - f)));
+    let lower = fractions
+        .iter()
+        .skip(1)
+        .map(|f| at(upper_len + (total - upper_len) * f));
+    let mut points: Vec<Point> = upper.chain(lower).collect();
+
+    // Keep the end points exact, so a closed trailing edge stays closed.
+    let last = points.len() - 1;
+    points[0] = start;
+    points[last] = end;
+    points[n] = segs[le].eval(1.);
+    points
+}
+
+/// The changes made to a contour by [`normalize`].
+#[derive(Debug, Clone, Copy)]
+pub struct Normalization {
+    /// Position of the leading edge before normalizing.
+    pub leading_edge: Point,
+    /// Rotation applied to level the chord line, in degrees.
+    pub rotation: f64,
+    /// Scale factor applied to reach unit chord.
+    pub scale: f64,
+}
+
+impl fmt::Display for Normalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "moved leading edge from ({:.4}, {:.4}) to the origin, rotated by {:.3} degrees, scaled by {:.4}",
+            self.leading_edge.x, self.leading_edge.y, self.rotation, self.scale
+        )
+    }
+}
+
+/// Translates, derotates and scales a contour so that the leading edge is at
+/// the origin and the trailing edge is at (1, 0).
+///
+/// The trailing edge is the midpoint of the first and last points, and the
+/// leading edge the point farthest from it.
+pub fn normalize(points: &[Point]) -> (Vec<Point>, Normalization) {
+    let te = points[0].midpoint(points[points.len() - 1]);
+    let le = points[leading_edge_index(points)];
+
+    let chord = te - le;
+    let (scale, angle) = (1. / chord.hypot(), chord.atan2());
+    let transform = kurbo::Affine::scale(scale)
+        * kurbo::Affine::rotate(-angle)
+        * kurbo::Affine::translate(-le.to_vec2());
+
+    let normalization = Normalization {
+        leading_edge: le,
+        rotation: -angle.to_degrees(),
+        scale,
+    };
+    (
+        points.iter().map(|&p| transform * p).collect(),
+        normalization,
+    )
+}
+
+/// Shape of the trailing edge closing a contour.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum TrailingEdge {
+    /// A flat face across the trailing edge
+    Blunt,
+    /// A semicircular cap across the trailing edge
+    Round,
+}
+
+/// Index of the leading edge: the point farthest from the trailing edge.
+pub fn leading_edge_index(points: &[Point]) -> usize {
+    let te = points[0].midpoint(points[points.len() - 1]);
+    (0..points.len())
+        .max_by(|&a, &b| {
+            points[a]
+                .distance_squared(te)
+                .total_cmp(&points[b].distance_squared(te))
+        })
+        .unwrap()
+}
+
+/// Opens up the trailing edge of a unit-chord contour to at least `thickness`,
+/// blending the extra thickness in smoothly over the aft `blend` fraction of
+/// the chord, then closes it with the given cap.
+pub fn thicken_te(points: &[Point], thickness: f64, blend: f64, cap: TrailingEdge) -> Vec<Point> {
+    let gap = points[0].y - points[points.len() - 1].y;
+    let extra = kurbo::Vec2::new(0., (thickness - gap).max(0.) / 2.);
+    let mut out = shift_te(points, extra, -extra, blend);
+
+    if cap == TrailingEdge::Round {
+        const SEGMENTS: usize = 8;
+        let (upper, lower) = (out[0], out[out.len() - 1]);
+        let center = upper.midpoint(lower);
+        let radius = (upper - lower) / 2.;
+        // Perpendicular to the trailing edge face, pointing aft.
+        let aft = kurbo::Vec2::new(radius.y, -radius.x);
+        for i in 1..SEGMENTS {
+            let theta = std::f64::consts::PI * i as f64 / SEGMENTS as f64;
+            out.push(center - radius * theta.cos() + aft * theta.sin());
+        }
+    }
+    out
+}
+
+/// Closes an open trailing edge by pulling both surfaces together to the
+/// midpoint of the trailing edge, over the aft `blend` fraction of the chord.
+pub fn close_te(points: &[Point], blend: f64) -> Vec<Point> {
+    let (upper, lower) = (points[0], points[points.len() - 1]);
+    let mid = upper.midpoint(lower);
+    let mut out = shift_te(points, mid - upper, mid - lower, blend);
+    let last = out.len() - 1;
+    out[last] = out[0];
+    out
+}
+
+/// Moves the trailing edge of the upper and lower surfaces by the given
+/// offsets, fading the movement in smoothly over the aft `blend` fraction of
+/// the chord.
+fn shift_te(points: &[Point], upper: kurbo::Vec2, lower: kurbo::Vec2, blend: f64) -> Vec<Point> {
+    let le = leading_edge_index(points);
+    let blend = blend.clamp(1e-6, 1.);
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, &p)| {
+            let t = ((p.x - (1. - blend)) / blend).clamp(0., 1.);
+            let weight = t * t * (3. - 2. * t);
+            p + if i <= le { upper } else { lower } * weight
+        })
+        .collect()
+}
+
+/// Offsets a unit-chord contour inwards by `distance`, for the inside of a
+/// hollow wing.
+///
+/// Towards the trailing edge, where the section gets too thin to leave a gap
+/// of at least `distance` inside the skin, the skin is left solid and the
+/// contour is cut off square. The returned contour has an open trailing edge.
+pub fn offset(points: &[Point], distance: f64) -> Vec<Point> {
+    let mut points = points.to_vec();
+    points.dedup();
+    let n = points.len();
+
+    // Selig order runs anticlockwise, so the inside is to the left.
+    let inner: Vec<Point> = (0..n)
+        .map(|i| {
+            let tangent = (points[(i + 1).min(n - 1)] - points[i.saturating_sub(1)]).normalize();
+            points[i] + kurbo::Vec2::new(-tangent.y, tangent.x) * distance
+        })
+        .collect();
+
+    // Where the contour curves tighter than the offset, such as around a
+    // sharp leading edge, the offset loops back on itself. Those points end
+    // up closer to the contour than `distance`, and are dropped.
+    let clear = |p: &Point| {
+        points.windows(2).all(|w| {
+            let (a, b) = (w[0], w[1]);
+            let t = ((*p - a).dot(b - a) / (b - a).hypot2()).clamp(0., 1.);
+            p.distance(a.lerp(b, t)) >= distance * 0.999
+        })
+    };
+    let le = leading_edge_index(&points);
+    let upper: Vec<Point> = inner[..=le].iter().copied().filter(clear).collect();
+    let lower: Vec<Point> = inner[le + 1..].iter().copied().filter(clear).collect();
+    let sorted = |surface: &[Point]| {
+        let mut surface = surface.to_vec();
+        surface.sort_by(|a, b| a.x.total_cmp(&b.x));
+        surface
+    };
+    let (upper_sorted, lower_sorted) = (sorted(&upper), sorted(&lower));
+    let gap = |x: f64| y_at(&upper_sorted, x) - y_at(&lower_sorted, x);
+
+    const STEPS: usize = 500;
+    let start = upper
+        .iter()
+        .chain(&lower)
+        .map(|p| p.x)
+        .fold(f64::MAX, f64::min);
+    let end = points[0].x.min(points[n - 1].x) - distance;
+    let cut = (0..=STEPS)
+        .map(|i| end - (end - start) * i as f64 / STEPS as f64)
+        .find(|&x| x > start && gap(x) >= distance)
+        .unwrap_or_else(|| {
+            panic!(
+                "wall thickness of {} chord leaves no room inside the section",
+                distance
+            )
+        });
+
+    let mut out = vec![Point::new(cut, y_at(&upper_sorted, cut))];
+    out.extend(upper.iter().filter(|p| p.x < cut));
+    out.extend(lower.iter().filter(|p| p.x < cut));
+    out.push(Point::new(cut, y_at(&lower_sorted, cut)));
+    out
+}
+
+/// Blends two contours with the same number of points, from `a` at `t = 0`
+/// to `b` at `t = 1`.
+pub fn lerp(a: &[Point], b: &[Point], t: f64) -> Vec<Point> {
+    if a.len() != b.len() {
+        panic!(
+            "cannot blend airfoils with {} and {} points",
+            a.len(),
+            b.len()
+        );
+    }
+    a.iter().zip(b).map(|(a, b)| a.lerp(*b, t)).collect()
+}
+
+/// Splits a contour at the leading edge into its upper and lower surfaces,
+/// both sorted from the leading edge to the trailing edge. Points aft of the
+/// trailing edge, such as a round trailing edge cap, are left out.
+fn surfaces(points: &[Point]) -> (Vec<Point>, Vec<Point>) {
+    let le = leading_edge_index(points);
+    let te = points[0].x.max(points[points.len() - 1].x);
+    let surface =
+        |points: &[Point]| -> Vec<Point> { points.iter().copied().filter(|p| p.x <= te).collect() };
+    let mut upper = surface(&points[..=le]);
+    let mut lower = surface(&points[le..]);
+    upper.sort_by(|a, b| a.x.total_cmp(&b.x));
+    lower.sort_by(|a, b| a.x.total_cmp(&b.x));
+    (upper, lower)
+}
+
+/// Height of a surface sorted by x at chord position `x`, clamped to its ends.
+fn y_at(surface: &[Point], x: f64) -> f64 {
+    let i = surface.partition_point(|p| p.x <= x);
+    if i == 0 {
+        return surface[0].y;
+    } else if i == surface.len() {
+        return surface[i - 1].y;
+    }
+    let (a, b) = (surface[i - 1], surface[i]);
+    a.y + (b.y - a.y) * (x - a.x) / (b.x - a.x)
+}
+
+/// Heights of the upper and lower surfaces of a contour at chord position `x`.
+pub fn surfaces_at(points: &[Point], x: f64) -> (f64, f64) {
+    let (upper, lower) = surfaces(points);
+    (y_at(&upper, x), y_at(&lower, x))
+}
+
+/// Height of the line halfway between the surfaces of a contour at chord
+/// position `x`.
+pub fn mean_line_at(points: &[Point], x: f64) -> f64 {
+    let (upper, lower) = surfaces_at(points, x);
+    (upper + lower) / 2.
+}
+
+/// Maximum thickness of a contour, as a fraction of its chord.
+pub fn thickness(points: &[Point]) -> f64 {
+    let (upper, lower) = surfaces(points);
+    points
+        .iter()
+        .map(|p| y_at(&upper, p.x) - y_at(&lower, p.x))
+        .fold(0., f64::max)
+}
+
+/// Scales the thickness of a contour by `factor`, keeping the line at
+/// `anchor(x)` fraction of the thickness fixed: 0.5 for the camber line, 1 for
+/// the upper surface.
+pub fn scale_thickness(points: &[Point], factor: f64, anchor: impl Fn(f64) -> f64) -> Vec<Point> {
+    let (upper, lower) = surfaces(points);
+    points
+        .iter()
+        .map(|p| {
+            let (yu, yl) = (y_at(&upper, p.x), y_at(&lower, p.x));
+            let reference = yl + anchor(p.x) * (yu - yl);
+            Point::new(p.x, reference + (p.y - reference) * factor)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(text: &str) -> Vec<Point> {
+        read_coordinates(text.as_bytes())
+    }
+
+    #[test]
+    fn reads_lednicer_in_selig_order() {
+        let points = read(
+            "NACA 0010 in Lednicer order\n\
+             3. 3.\n\
+             \n\
+             0. 0.\n\
+             0.5 0.05\n\
+             1. 0.\n\
+             \n\
+             0. 0.\n\
+             0.5 -0.05\n\
+             1. 0.\n",
+        );
+        assert_eq!(
+            points,
+            vec![
+                Point::new(1., 0.),
+                Point::new(0.5, 0.05),
+                Point::new(0., 0.),
+                Point::new(0.5, -0.05),
+                Point::new(1., 0.),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_selig_unchanged() {
+        let points = read("NACA 0010\n1. 0.\n0.5 0.05\n0. 0.\n0.5 -0.05\n1. 0.\n");
+        assert_eq!(points.len(), 5);
+        assert_eq!(points[1], Point::new(0.5, 0.05));
+        assert_eq!(points[3], Point::new(0.5, -0.05));
+    }
+
+    #[test]
+    #[should_panic(expected = "Lednicer header gives 3 upper and 3 lower")]
+    fn lednicer_count_mismatch() {
+        read("3. 3.\n0. 0.\n0.5 0.05\n1. 0.\n0. 0.\n1. 0.\n");
+    }
+
+    /// A diamond with its leading edge at the origin.
+    fn diamond() -> Vec<Point> {
+        vec![
+            Point::new(1., 0.),
+            Point::new(0.5, 0.1),
+            Point::new(0., 0.),
+            Point::new(0.5, -0.1),
+            Point::new(1., 0.),
+        ]
+    }
+
+    #[test]
+    fn repanel_keeps_the_ends_and_leading_edge() {
+        let path = path_from_points(&diamond());
+        for spacing in [Spacing::Cosine, Spacing::HalfCosine] {
+            let points = repanel(&path, 4, spacing);
+            assert_eq!(points.len(), 9);
+            assert_eq!(points[0], Point::new(1., 0.));
+            assert_eq!(points[4], Point::new(0., 0.));
+            assert_eq!(points[8], Point::new(1., 0.));
+            // Both surfaces are sampled the same way.
+            for i in 0..4 {
+                let (upper, lower) = (points[i], points[8 - i]);
+                assert!((upper.x - lower.x).abs() < 1e-9);
+                assert!((upper.y + lower.y).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn repanel_spacing() {
+        let path = path_from_points(&diamond());
+        // Cosine spacing puts the middle point halfway along each surface.
+        let points = repanel(&path, 4, Spacing::Cosine);
+        assert!(points[2].distance(Point::new(0.5, 0.1)) < 1e-9);
+        // Half-cosine spacing clusters the points at the leading edge.
+        let half = repanel(&path, 4, Spacing::HalfCosine);
+        assert!(half[3].x < points[3].x);
+        assert!(half[1].x < points[1].x);
+    }
+
+    #[test]
+    fn normalize_undoes_a_placement() {
+        let placement = kurbo::Affine::translate((3., -2.))
+            * kurbo::Affine::rotate(10f64.to_radians())
+            * kurbo::Affine::scale(2.);
+        let placed: Vec<Point> = diamond().iter().map(|&p| placement * p).collect();
+        let (points, normalization) = normalize(&placed);
+        for (p, q) in points.iter().zip(diamond()) {
+            assert!(p.distance(q) < 1e-12, "{:?} != {:?}", p, q);
+        }
+        assert!((normalization.scale - 0.5).abs() < 1e-12);
+        assert!((normalization.rotation + 10.).abs() < 1e-9);
+        assert!(normalization.leading_edge.distance(Point::new(3., -2.)) < 1e-12);
+    }
+}
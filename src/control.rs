@@ -0,0 +1,399 @@
+use crate::airfoil;
+use crate::wing::{self, Side, Station};
+use kurbo::Point;
+use std::str::FromStr;
+use truck_modeling::{Point3, Solid};
+
+/// How the leading edge of a control surface is shaped to clear the wing.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Hinge {
+    /// Hinged along the upper skin, with the lower side of the leading edge
+    /// bevelled so the surface can deflect down.
+    Top,
+    /// Hinged on the center line with a square leading edge, relying on the
+    /// gap for clearance.
+    Center,
+    /// Hinged on the center line, with both sides of the leading edge
+    /// bevelled so the surface can deflect either way.
+    Beveled,
+}
+
+/// A movable surface split from the trailing edge of the wing, such as an
+/// aileron, flap or elevator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControlSurface {
+    /// Chord fraction of the hinge line at the inboard end.
+    pub inboard: f64,
+    /// Chord fraction of the hinge line at the outboard end.
+    pub outboard: f64,
+    /// Span at which the surface starts, defaulting to the root.
+    pub start: Option<f64>,
+    /// Span at which the surface ends, defaulting to the tip.
+    pub end: Option<f64>,
+    /// Width of the gap around the surface.
+    pub gap: f64,
+    pub hinge: Hinge,
+}
+
+impl FromStr for ControlSurface {
+    type Err = String;
+
+    /// Parses a control surface given as
+    /// `inboard,outboard[,start[,end[,gap]]][,hinge]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields: Vec<&str> = s.split(',').map(str::trim).collect();
+
+        // The hinge style is the only field which isn't a number.
+        let hinge = match fields.last() {
+            Some(f) if f.parse::<f64>().is_err() => {
+                let hinge = clap::ValueEnum::from_str(f, true)
+                    .map_err(|_| format!("expected top, center or beveled hinge, got {:?}", f))?;
+                fields.pop();
+                hinge
+            }
+            _ => Hinge::Top,
+        };
+        if fields.len() < 2 || fields.len() > 5 {
+            return Err(format!(
+                "expected inboard,outboard[,start[,end[,gap]]][,hinge], got {:?}",
+                s
+            ));
+        }
+
+        let number = |i: usize| -> Result<Option<f64>, String> {
+            fields
+                .get(i)
+                .map(|f| {
+                    f.parse()
+                        .map_err(|_| format!("invalid number {:?} in control surface {:?}", f, s))
+                })
+                .transpose()
+        };
+
+        Ok(ControlSurface {
+            inboard: wing::parse_chord_fraction(fields[0])?,
+            outboard: wing::parse_chord_fraction(fields[1])?,
+            start: number(2)?,
+            end: number(3)?,
+            gap: number(4)?.unwrap_or(1.),
+            hinge,
+        })
+    }
+}
+
+/// Parses a rudder, given as for a control surface. Rudders deflect both
+/// ways, so are `beveled` unless given another hinge.
+pub fn parse_rudder(s: &str) -> Result<ControlSurface, String> {
+    let mut rudder: ControlSurface = s.parse()?;
+    let hinged = s
+        .rsplit(',')
+        .next()
+        .is_some_and(|f| f.trim().parse::<f64>().is_err());
+    if !hinged {
+        rudder.hinge = Hinge::Beveled;
+    }
+    Ok(rudder)
+}
+
+/// Which side of the hinge gap a cutting tool is for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Part {
+    /// Everything aft of the gap, cut out of the wing.
+    Cutout,
+    /// The control surface itself, taken from the wing.
+    Surface,
+}
+
+/// How far tools reach past the wing's surfaces, as a fraction of the chord.
+const REACH: f64 = 0.5;
+
+impl ControlSurface {
+    /// The tool which cuts out one side of the hinge gap, along the wing made
+    /// of `stations`. When `capped`, a winglet or tip cap carries on past the
+    /// last station.
+    pub fn tool(
+        &self,
+        part: Part,
+        stations: &[Station],
+        profiles: &[Vec<Point>],
+        twist_axis: f64,
+        capped: bool,
+    ) -> Tool {
+        let (root, tip) = (stations[0].span, stations[stations.len() - 1].span);
+        let start = self.start.unwrap_or(root).max(root);
+        let end = self.end.unwrap_or(tip).min(tip);
+        // A surface can't run out through a winglet or tip cap, so it stops
+        // short of the tip, leaving the width of the gap beyond its cutout.
+        let end = match capped {
+            true => end.min(tip - 1.5 * self.gap),
+            false => end,
+        };
+        if start >= end {
+            panic!("control surface must end further along the span than it starts");
+        }
+
+        // The cutout is wider than the surface by the gap, except where the
+        // surface runs out of the root or tip.
+        let side = match part {
+            Part::Cutout => -0.5,
+            Part::Surface => 0.5,
+        };
+        let (open_start, open_end) = (start <= root, end >= tip);
+        let from = if open_start {
+            root
+        } else {
+            (start + side * self.gap).max(root)
+        };
+        let to = if open_end {
+            tip
+        } else {
+            (end - side * self.gap).min(tip)
+        };
+
+        let mut spans = vec![from];
+        spans.extend(
+            stations
+                .iter()
+                .map(|s| s.span)
+                .filter(|&span| span > from && span < to),
+        );
+        spans.push(to);
+
+        let sections = spans
+            .into_iter()
+            .map(|span| {
+                let (station, profile) = wing::section_at(stations, profiles, span);
+                let eta = ((span - start) / (end - start)).clamp(0., 1.);
+                let hinge = self.inboard + (self.outboard - self.inboard) * eta;
+                let x = hinge + side * self.gap / station.chord;
+                let outline = self.outline(part, &profile, x);
+
+                // Only the hinge line follows the twist of the wing. Keeping
+                // the outline itself square to the wing leaves the faces of
+                // the tool flat, which the cut needs where they cross the
+                // twisted skins.
+                let untwisted = Station {
+                    twist: 0.,
+                    ..station.clone()
+                };
+                let place = |station: &Station, p: Point| {
+                    let p =
+                        station.transform(twist_axis) * Point3::new(p.x, p.y, 0.).to_homogeneous();
+                    Point3::from_homogeneous(p)
+                };
+                let (upper, lower) = airfoil::surfaces_at(&profile, x);
+                let anchor = Point::new(x, (upper + lower) / 2.);
+                let offset = place(&station, anchor) - place(&untwisted, anchor);
+                outline
+                    .iter()
+                    .map(|&p| place(&untwisted, p) + offset)
+                    .collect()
+            })
+            .collect();
+        Tool {
+            sections,
+            open_start,
+            open_end,
+        }
+    }
+
+    /// Outline of a tool in a unit-chord section, with its forward face at
+    /// chord position `x`.
+    fn outline(&self, part: Part, profile: &[Point], x: f64) -> Vec<Point> {
+        let (upper, lower) = airfoil::surfaces_at(profile, x);
+        let (aft, top, bottom) = (1. + REACH, upper + REACH, lower - REACH);
+        let point = Point::new;
+
+        // Bevels are at 45 degrees, carried on past the skins so none of the
+        // tool's edges lie on them.
+        let margin = (upper - lower) / 2.;
+        let front = match (part, self.hinge) {
+            (Part::Cutout, _) | (Part::Surface, Hinge::Center) => {
+                vec![point(x, top), point(x, bottom)]
+            }
+            (Part::Surface, Hinge::Top) => vec![
+                point(x - margin, top),
+                point(x - margin, upper + margin),
+                point(x + upper - lower + margin, lower - margin),
+                point(x + upper - lower + margin, bottom),
+            ],
+            (Part::Surface, Hinge::Beveled) => {
+                let mid = (upper + lower) / 2.;
+                let bevel = (upper - lower) / 2. + margin;
+                vec![
+                    point(x + bevel, top),
+                    point(x + bevel, mid + bevel),
+                    point(x, mid),
+                    point(x + bevel, mid - bevel),
+                    point(x + bevel, bottom),
+                ]
+            }
+        };
+        let mut outline = vec![point(aft, bottom), point(aft, top)];
+        outline.extend(front);
+        outline
+    }
+}
+
+/// A cutting tool lofted through polygons placed along the wing.
+#[derive(Debug, Clone)]
+pub struct Tool {
+    /// Outlines of the tool, in planes parallel to the root.
+    pub sections: Vec<Vec<Point3>>,
+    /// Whether the tool runs out past the first section.
+    pub open_start: bool,
+    /// Whether the tool runs out past the last section.
+    pub open_end: bool,
+}
+
+impl Tool {
+    /// The same tool in the mirrored wing.
+    pub fn mirrored(&self) -> Tool {
+        let sections = self.sections.iter().rev().map(|s| wing::mirror_points(s));
+        Tool {
+            sections: sections.collect(),
+            open_start: self.open_end,
+            open_end: self.open_start,
+        }
+    }
+
+    /// Joins a tool which runs out of the root with its mirror image, for a
+    /// fused wing.
+    pub fn fused(&self) -> Tool {
+        let mut joined = self.mirrored();
+        joined.sections.extend(self.sections[1..].iter().cloned());
+        joined.open_end = self.open_end;
+        joined
+    }
+
+    /// The solid swept by the tool, carried on past its open ends.
+    pub fn solid(&self) -> Solid {
+        // The end sections are moved out along the tool rather than added,
+        // so none lie in the plane of the root or tip face.
+        let overhang = |end: &Vec<Point3>, next: &Vec<Point3>| -> Vec<Point3> {
+            // About a chord, well clear of the end of the wing.
+            let reach = (end[0].x - end[end.len() - 1].x).abs();
+            let scale = reach / (end[0].z - next[0].z).abs();
+            end.iter()
+                .zip(next)
+                .map(|(&e, &n)| e + (e - n) * scale)
+                .collect()
+        };
+        let mut sections = self.sections.clone();
+        let n = sections.len() - 1;
+        if self.open_start {
+            sections[0] = overhang(&self.sections[0], &self.sections[1]);
+        }
+        if self.open_end {
+            sections[n] = overhang(&self.sections[n], &self.sections[n - 1]);
+        }
+        let wires: Vec<_> = sections.iter().map(|s| wing::polygon(s)).collect();
+        wing::loft(&wires)
+    }
+}
+
+/// The tools for one side of the hinge gap of each of the `surfaces`, along
+/// one side of the wing made of `stations`, or both sides for `Side::Both`.
+/// When `capped`, a winglet or tip cap carries on past the last station.
+pub fn tools(
+    surfaces: &[ControlSurface],
+    part: Part,
+    stations: &[Station],
+    profiles: &[Vec<Point>],
+    twist_axis: f64,
+    capped: bool,
+    side: Side,
+) -> Vec<Solid> {
+    surfaces
+        .iter()
+        .map(|surface| surface.tool(part, stations, profiles, twist_axis, capped))
+        .flat_map(|tool| match side {
+            Side::Left => vec![tool.solid()],
+            Side::Right => vec![tool.mirrored().solid()],
+            // Tools running out of the root are joined with their mirror
+            // images, so the surface runs across it.
+            Side::Both => match tool.open_start {
+                true => vec![tool.fused().solid()],
+                false => vec![tool.solid(), tool.mirrored().solid()],
+            },
+        })
+        .collect()
+}
+
+/// Splits the control surfaces from a piece of the wing, with the `surfaces`
+/// tools taking each surface from it and the `cutouts` cutting it out of the
+/// piece with the gap around it. Gives the rest of the piece, and the
+/// surfaces.
+pub fn split_all(solid: Solid, surfaces: &[Solid], cutouts: &[Solid]) -> (Solid, Vec<Solid>) {
+    let surfaces = surfaces
+        .iter()
+        .map(|surface| wing::intersect(&solid, surface))
+        .collect();
+    let solid = cutouts
+        .iter()
+        .fold(solid, |solid, cut| wing::subtract(&solid, cut));
+    (solid, surfaces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_control_surface() {
+        let surface: ControlSurface = "0.7,0.75".parse().unwrap();
+        assert_eq!(
+            surface,
+            ControlSurface {
+                inboard: 0.7,
+                outboard: 0.75,
+                start: None,
+                end: None,
+                gap: 1.,
+                hinge: Hinge::Top,
+            }
+        );
+
+        let surface: ControlSurface = "0.7, 0.75, 50, 250, 0.5, beveled".parse().unwrap();
+        assert_eq!((surface.start, surface.end), (Some(50.), Some(250.)));
+        assert_eq!(surface.gap, 0.5);
+        assert_eq!(surface.hinge, Hinge::Beveled);
+    }
+
+    #[test]
+    fn parse_control_surface_errors() {
+        assert!("0.7".parse::<ControlSurface>().is_err());
+        assert!("0.7,0.75,center,50".parse::<ControlSurface>().is_err());
+        assert!("0.7,0.75,bottom".parse::<ControlSurface>().is_err());
+        assert!("0.7,0.75,1,2,3,4".parse::<ControlSurface>().is_err());
+    }
+
+    #[test]
+    fn stops_short_of_a_capped_tip() {
+        let stations: Vec<Station> = ["0,50", "100,40"].map(|s| s.parse().unwrap()).to_vec();
+        let profile = crate::naca::generate("0012", 40, false);
+        let profiles = vec![profile.clone(), profile];
+        let surface: ControlSurface = "0.75,0.75".parse().unwrap();
+        let tool = |part, capped| surface.tool(part, &stations, &profiles, 0.25, capped);
+
+        // Open-ended tools run out through a plain tip...
+        for part in [Part::Cutout, Part::Surface] {
+            assert!(tool(part, false).open_end);
+        }
+        // ...but past a winglet or tip cap they stop short, with the
+        // cutout the gap clear of the tip and the surface inside it.
+        let ends = [(Part::Cutout, 99.), (Part::Surface, 98.)];
+        for (part, end) in ends {
+            let tool = tool(part, true);
+            assert!(!tool.open_end);
+            let last = tool.sections.last().unwrap();
+            assert!(last.iter().all(|p| (p.z - end).abs() < 1e-9));
+        }
+    }
+
+    #[test]
+    fn rudders_are_beveled() {
+        assert_eq!(parse_rudder("0.7,0.7").unwrap().hinge, Hinge::Beveled);
+        assert_eq!(parse_rudder("0.7,0.7,center").unwrap().hinge, Hinge::Center);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::airfoil::cosine_spacing;
+use kurbo::Point;
+
+/// An airfoil defined by Class-Shape Transformation (Kulfan) parameters.
+#[derive(Debug, Clone)]
+pub struct Cst {
+    /// Bernstein coefficients of the upper surface shape function.
+    pub upper: Vec<f64>,
+    /// Bernstein coefficients of the lower surface shape function (usually negative).
+    pub lower: Vec<f64>,
+    /// Leading edge class function exponent (0.5 for a round nose).
+    pub n1: f64,
+    /// Trailing edge class function exponent (1.0 for a sharp trailing edge).
+    pub n2: f64,
+    /// Total trailing edge thickness, as a fraction of chord.
+    pub te_thickness: f64,
+}
+
+impl Cst {
+    /// Samples the upper and lower surfaces at `points` intervals with cosine
+    /// spacing, returning the contour in Selig order.
+    pub fn contour(&self, points: usize) -> Vec<Point> {
+        if self.upper.is_empty() || self.lower.is_empty() {
+            panic!("CST coefficients are required for both surfaces");
+        }
+
+        let xs = cosine_spacing(points.max(2));
+
+        let surface = |coeffs: &[f64], x: f64, te_offset: f64| {
+            let class = x.powf(self.n1) * (1. - x).powf(self.n2);
+            Point::new(x, class * shape(coeffs, x) + x * te_offset)
+        };
+
+        let te = self.te_thickness / 2.;
+        let upper = xs.iter().rev().map(|&x| surface(&self.upper, x, te));
+        let lower = xs.iter().skip(1).map(|&x| surface(&self.lower, x, -te));
+        let mut contour: Vec<Point> = upper.chain(lower).collect();
+
+        if self.te_thickness == 0. {
+            let last = contour.len() - 1;
+            contour[last] = contour[0];
+        }
+        contour
+    }
+}
+
+/// Evaluates the Bernstein polynomial shape function with the given coefficients.
+fn shape(coeffs: &[f64], x: f64) -> f64 {
+    let n = coeffs.len() - 1;
+    let mut binomial = 1.;
+    let mut sum = 0.;
+    for (i, a) in coeffs.iter().enumerate() {
+        sum += a * binomial * x.powi(i as i32) * (1. - x).powi((n - i) as i32);
+        binomial = binomial * (n - i) as f64 / (i + 1) as f64;
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cst(upper: &[f64], lower: &[f64], te_thickness: f64) -> Cst {
+        Cst {
+            upper: upper.to_vec(),
+            lower: lower.to_vec(),
+            n1: 0.5,
+            n2: 1.,
+            te_thickness,
+        }
+    }
+
+    #[test]
+    fn unit_shape_function() {
+        // Equal coefficients give a flat shape function, as the Bernstein
+        // polynomials add up to one.
+        for x in [0., 0.2, 0.5, 0.9, 1.] {
+            assert!((shape(&[1., 1., 1., 1.], x) - 1.).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn contour() {
+        // With one coefficient each surface is a * sqrt(x) * (1 - x), and
+        // 60 cosine-spaced points put one at x = 0.25.
+        let points = cst(&[0.2], &[-0.1], 0.).contour(60);
+        assert_eq!(points.len(), 121);
+        assert_eq!(points[60], Point::new(0., 0.));
+        assert_eq!(points[0], points[120]);
+        let at = |x: f64, upper: bool| {
+            points
+                .iter()
+                .find(|p| (p.x - x).abs() < 1e-12 && (p.y > 0.) == upper)
+                .unwrap()
+                .y
+        };
+        assert!((at(0.25, true) - 0.075).abs() < 1e-12);
+        assert!((at(0.25, false) + 0.0375).abs() < 1e-12);
+    }
+
+    #[test]
+    fn trailing_edge_thickness() {
+        let points = cst(&[0.2], &[-0.2], 0.004).contour(40);
+        assert!((points[0].y - 0.002).abs() < 1e-12);
+        assert!((points[points.len() - 1].y + 0.002).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::airfoil;
+use kurbo::{Affine, Point, Vec2};
+use std::str::FromStr;
+
+/// An element split from a section ahead of or behind the main element,
+/// such as a slat or slotted flap. Sizes are fractions of the local chord.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element {
+    /// Chord of the element where it's split from the section.
+    pub chord: f64,
+    /// Gap between the element and the main element.
+    pub gap: f64,
+    /// How far the element and the main element overlap along the chord.
+    pub overlap: f64,
+    /// Deflection in degrees: nose down for a slat, or trailing edge down for
+    /// a flap.
+    pub deflection: f64,
+}
+
+impl FromStr for Element {
+    type Err = String;
+
+    /// Parses an element given as `chord[,gap[,overlap[,deflection]]]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split(',').map(str::trim).collect();
+        if fields.len() > 4 {
+            return Err(format!(
+                "expected chord[,gap[,overlap[,deflection]]], got {:?}",
+                s
+            ));
+        }
+        let number = |i: usize| -> Result<Option<f64>, String> {
+            fields
+                .get(i)
+                .map(|f| {
+                    f.parse()
+                        .map_err(|_| format!("invalid number {:?} in element {:?}", f, s))
+                })
+                .transpose()
+        };
+
+        let chord = number(0)?.unwrap();
+        if chord <= 0. || chord >= 0.5 {
+            return Err(format!(
+                "element chord must be between 0 and 0.5, got {}",
+                chord
+            ));
+        }
+        Ok(Element {
+            chord,
+            gap: number(1)?.unwrap_or(0.015),
+            overlap: number(2)?.unwrap_or(0.01),
+            deflection: number(3)?.unwrap_or(0.),
+        })
+    }
+}
+
+/// Points along each run of surface in an element's contour.
+const SURFACE_POINTS: usize = 40;
+
+/// Points along a cut between elements.
+const CUT_POINTS: usize = 24;
+
+/// Where the cut separating a slat runs, as fractions of the slat's chord:
+/// the front of the main element's new nose, and where the cut meets the
+/// lower surface. It meets the upper surface at the slat's chord.
+const SLAT_CUT: (f64, f64) = (0.4, 0.6);
+
+/// Where the cut separating a flap meets the upper and lower surfaces, as
+/// fractions of the flap's chord aft of its nose. The upper one is the
+/// trailing edge of the main element, which shrouds the flap's nose.
+const FLAP_CUT: (f64, f64) = (0.35, 0.1);
+
+/// Splits a unit-chord contour into its main element and any `slat` and
+/// `flap`, in that order, each a closed contour with the same winding.
+///
+/// The elements are resampled along the surfaces of the contour with the
+/// same number of points whatever its shape, so the elements of different
+/// sections can be lofted together. Anything aft of the trailing edge, such
+/// as a round cap, is left off. Without a slat or flap the contour is
+/// returned as it is.
+pub fn split(profile: &[Point], slat: Option<&Element>, flap: Option<&Element>) -> Vec<Vec<Point>> {
+    if slat.is_none() && flap.is_none() {
+        return vec![profile.to_vec()];
+    }
+    let le = profile[airfoil::leading_edge_index(profile)].x;
+    let te = profile[0].x.max(profile[profile.len() - 1].x);
+    let fore = slat.map(|slat| Cut {
+        front: slat.chord * SLAT_CUT.0,
+        upper: slat.chord,
+        lower: slat.chord * SLAT_CUT.1,
+    });
+    let aft = flap.map(|flap| Cut {
+        front: 1. - flap.chord,
+        upper: 1. - flap.chord * (1. - FLAP_CUT.0),
+        lower: 1. - flap.chord * (1. - FLAP_CUT.1),
+    });
+
+    // The main element runs forward along the upper surface from its
+    // trailing edge, round its nose and back along the lower surface, then
+    // up the cove under its trailing edge when there's a flap.
+    let tail = aft.as_ref().map_or((te, te), |cut| (cut.upper, cut.lower));
+    let mut main = match &fore {
+        Some(cut) => {
+            let mut points = surface(profile, tail.0, cut.upper, true);
+            points.extend(cut.points(profile).into_iter().rev());
+            points.extend(surface(profile, cut.lower, tail.1, false));
+            points
+        }
+        None => {
+            let mut points = surface(profile, tail.0, le, true);
+            points.extend(&surface(profile, le, tail.1, false)[1..]);
+            points
+        }
+    };
+    if let Some(cut) = &aft {
+        main.extend(cut.points(profile));
+    }
+    let mut elements = vec![main];
+
+    if let (Some(slat), Some(cut)) = (slat, &fore) {
+        let mut points = surface(profile, cut.upper, le, true);
+        points.extend(&surface(profile, le, cut.lower, false)[1..]);
+        points.extend(cut.points(profile));
+
+        // The slat is turned about its trailing edge, then moved out until
+        // its trailing edge is the gap clear of the main element's nose.
+        let lip = points[0];
+        let turned = Affine::rotate_about(slat.deflection.to_radians(), lip);
+        let x = cut.front + slat.overlap;
+        let y = airfoil::surfaces_at(&elements[0], x).0 + slat.gap;
+        let moved = Affine::translate(Point::new(x, y) - lip) * turned;
+        elements.push(points.into_iter().map(|p| moved * p).collect());
+    }
+
+    if let (Some(flap), Some(cut)) = (flap, &aft) {
+        let mut points = surface(profile, te, cut.upper, true);
+        let cove = cut.points(profile);
+        let nose = cove[cove.len() / 2];
+        points.extend(cove.into_iter().rev());
+        points.extend(surface(profile, cut.lower, te, false));
+
+        // The flap is turned about its nose, then moved under the main
+        // element's trailing edge, overlapping it and the gap below it.
+        let turned = Affine::rotate_about(-flap.deflection.to_radians(), nose);
+        let points: Vec<Point> = points.into_iter().map(|p| turned * p).collect();
+        let lip = Point::new(cut.upper, airfoil::surfaces_at(profile, cut.upper).0);
+        let nose = turned * nose;
+        let dx = lip.x - flap.overlap - nose.x;
+        let shifted: Vec<Point> = points.iter().map(|&p| p + Vec2::new(dx, 0.)).collect();
+        let top = airfoil::surfaces_at(&shifted, lip.x.max(nose.x + dx)).0;
+        let dy = lip.y - flap.gap - top;
+        elements.push(shifted.into_iter().map(|p| p + Vec2::new(0., dy)).collect());
+    }
+    elements
+}
+
+/// Splits the `profiles` of a wing's sections into the main element and any
+/// `slat` and `flap`, giving the profiles of each element along the wing
+/// with the main element first.
+pub fn split_all(
+    profiles: &[Vec<Point>],
+    slat: Option<&Element>,
+    flap: Option<&Element>,
+) -> Vec<Vec<Vec<Point>>> {
+    let sections: Vec<_> = profiles
+        .iter()
+        .map(|profile| split(profile, slat, flap))
+        .collect();
+    (0..sections[0].len())
+        .map(|i| {
+            sections
+                .iter()
+                .map(|elements| elements[i].clone())
+                .collect()
+        })
+        .collect()
+}
+
+/// Points along the upper or lower surface of a contour from chord position
+/// `from` to `to`, closer together towards both ends.
+fn surface(profile: &[Point], from: f64, to: f64, upper: bool) -> Vec<Point> {
+    airfoil::cosine_spacing(SURFACE_POINTS)
+        .into_iter()
+        .map(|t| {
+            let x = from + (to - from) * t;
+            let (y_upper, y_lower) = airfoil::surfaces_at(profile, x);
+            Point::new(x, if upper { y_upper } else { y_lower })
+        })
+        .collect()
+}
+
+/// A cut through a section from the lower surface to the upper, bulging
+/// forward to `front` halfway between them. It meets the surfaces at the
+/// chord positions `upper` and `lower`, running along them.
+struct Cut {
+    front: f64,
+    upper: f64,
+    lower: f64,
+}
+
+impl Cut {
+    /// Points along the cut in a section, from the lower surface to the
+    /// upper, leaving out the ends on the surfaces.
+    fn points(&self, profile: &[Point]) -> Vec<Point> {
+        (1..CUT_POINTS)
+            .map(|i| {
+                let s = i as f64 / CUT_POINTS as f64;
+                // Each half of the cut is a quarter of an ellipse.
+                let (end, t) = match s < 0.5 {
+                    true => (self.lower, 1. - 2. * s),
+                    false => (self.upper, 2. * s - 1.),
+                };
+                let x = self.front + (end - self.front) * (1. - (1. - t * t).sqrt());
+                let (upper, lower) = airfoil::surfaces_at(profile, x);
+                Point::new(x, lower + s * (upper - lower))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_element() {
+        let element: Element = "0.3".parse().unwrap();
+        assert_eq!(
+            element,
+            Element {
+                chord: 0.3,
+                gap: 0.015,
+                overlap: 0.01,
+                deflection: 0.,
+            }
+        );
+
+        let element: Element = "0.3, 0.02, 0.005, 25".parse().unwrap();
+        assert_eq!((element.gap, element.overlap), (0.02, 0.005));
+        assert_eq!(element.deflection, 25.);
+    }
+
+    #[test]
+    fn parse_element_errors() {
+        assert!("0.6".parse::<Element>().is_err());
+        assert!("0".parse::<Element>().is_err());
+        assert!("0.3,wide".parse::<Element>().is_err());
+        assert!("0.3,0.02,0.005,25,1".parse::<Element>().is_err());
+    }
+}
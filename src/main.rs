@@ -1,43 +1,335 @@
-use clap::Parser;
-use kurbo::{BezPath, PathEl};
-use std::collections::HashMap;
+use clap::{ArgGroup, CommandFactory, Parser};
+use kurbo::BezPath;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::BufReader;
 use truck_modeling::*;
 
+mod airfoil;
+mod control;
+mod cst;
+mod element;
+mod naca;
+mod naca6;
+mod rib;
+mod segment;
+mod servo;
+mod spar;
+mod spline;
+mod wing;
+
 /// Simple program to generate a wing model given airfoil parameters
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
+#[command(group(ArgGroup::new("airfoil")))]
+#[command(group(ArgGroup::new("surfaces").multiple(true)))]
 struct Args {
-    /// Width of each wing
-    #[arg(short = 'w', long)]
-    semi_wingspan: f64,
+    /// Width of each wing, from the root to the tip
+    #[arg(short = 'w', long, required_unless_present_any = ["station", "stations", "chord_table"])]
+    semi_wingspan: Option<f64>,
 
     /// Distance to sweep the wing back
     #[arg(short, long, default_value_t = 0.0)]
     sweep: f64,
 
+    /// Angle in degrees to sweep the wing back, instead of a distance
+    #[arg(long, conflicts_with = "sweep", allow_negative_numbers = true)]
+    sweep_angle: Option<f64>,
+
+    /// Chord line the sweep angle is measured on: le, c/4, te or a chord fraction
+    #[arg(long, default_value = "le", value_parser = wing::parse_chord_fraction)]
+    sweep_reference: f64,
+
     /// Root chord length
-    #[arg(short, long)]
-    root_chord: f64,
+    #[arg(short, long, required_unless_present_any = ["station", "stations", "chord_table"])]
+    root_chord: Option<f64>,
 
     /// Tip chord length
-    #[arg(short, long)]
-    tip_chord: f64,
+    #[arg(short, long, required_unless_present_any = ["station", "stations", "chord_table"])]
+    tip_chord: Option<f64>,
+
+    /// Spanwise chord distribution between the root and tip chords
+    #[arg(long, value_enum, default_value_t = wing::Planform::Linear)]
+    planform: wing::Planform,
+
+    /// Number of sections used to follow an elliptical planform
+    #[arg(long, default_value_t = 16)]
+    sections: usize,
+
+    /// File of span,chord pairs giving the chord distribution from the root to
+    /// the tip, instead of the semi-wingspan, root and tip chords
+    #[arg(long, conflicts_with_all = ["semi_wingspan", "root_chord", "tip_chord", "planform"])]
+    chord_table: Option<String>,
+
+    /// Airfoil of the tip section, blended linearly from the root airfoil. May be
+    /// a NACA designation (e.g. naca2410) or a coordinate file
+    #[arg(long)]
+    tip_airfoil: Option<String>,
+
+    /// Twist of the tip section in degrees, positive nose up (negative for washout)
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    tip_twist: f64,
+
+    /// Fraction of the chord about which sections are twisted
+    #[arg(long, default_value_t = 0.25)]
+    twist_axis: f64,
+
+    /// Dihedral angle of the wing in degrees
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    dihedral: f64,
+
+    /// Wing station as span,chord[,le_offset[,twist[,dihedral]]][,airfoil], in
+    /// order from the root. Replaces the root and tip options when given. The
+    /// dihedral applies to the panel ending at the station, and the airfoil may
+    /// be a NACA designation (e.g. naca2412) or a coordinate file.
+    #[arg(long, conflicts_with_all = ["semi_wingspan", "root_chord", "tip_chord", "sweep", "sweep_angle", "tip_airfoil", "tip_twist", "dihedral", "planform", "chord_table", "stations"])]
+    station: Vec<wing::Station>,
+
+    /// File of wing stations, one per line in the same format as --station
+    #[arg(long, conflicts_with_all = ["semi_wingspan", "root_chord", "tip_chord", "sweep", "sweep_angle", "tip_airfoil", "tip_twist", "dihedral", "planform", "chord_table"])]
+    stations: Option<String>,
+
+    /// Shape of the wing tip
+    #[arg(long, value_enum, default_value_t = wing::TipCap::Flat)]
+    tip_cap: wing::TipCap,
+
+    /// Spanwise length of a shaped wing tip, beyond the tip section
+    #[arg(long)]
+    tip_cap_length: Option<f64>,
+
+    /// Add a winglet at the tip, with a straight panel of this length
+    #[arg(long)]
+    winglet_height: Option<f64>,
+
+    /// Cant angle of the winglet in degrees from vertical, positive leaning outboard
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    winglet_cant: f64,
+
+    /// Toe angle of the winglet in degrees, positive with the leading edge outboard
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    winglet_toe: f64,
+
+    /// Leading edge sweep angle of the winglet in degrees
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    winglet_sweep: f64,
+
+    /// Ratio of the winglet tip chord to the wing tip chord
+    #[arg(long, default_value_t = 1.0)]
+    winglet_taper: f64,
+
+    /// Radius of the blended transition from the wing to the winglet
+    /// [default: twice the tip thickness]
+    #[arg(long)]
+    winglet_radius: Option<f64>,
+
+    /// Airfoil of the winglet, blended from the tip airfoil through the
+    /// transition. May be a NACA designation or a coordinate file
+    #[arg(long)]
+    winglet_airfoil: Option<String>,
+
+    /// Split a slat from the leading edge, given as
+    /// `chord[,gap[,overlap[,deflection]]]` in fractions of the local chord.
+    /// The slat's trailing edge sits the gap (default 0.015) above the main
+    /// element, overlapping its nose by the overlap (default 0.01), with the
+    /// slat turned nose down by the deflection in degrees (default 0)
+    #[arg(long, conflicts_with = "wall")]
+    slat: Option<element::Element>,
+
+    /// Split a slotted flap from the trailing edge, given as
+    /// `chord[,gap[,overlap[,deflection]]]` as for --slat. The flap's nose
+    /// sits the gap below the main element's trailing edge and the overlap
+    /// forward of it, with the flap turned trailing edge down by the
+    /// deflection
+    #[arg(long, conflicts_with_all = ["wall", "control_surface"])]
+    slotted_flap: Option<element::Element>,
+
+    /// Make the wing hollow, with a skin of this thickness. The skin is left
+    /// solid where the section is too thin to hollow out
+    #[arg(long)]
+    wall: Option<f64>,
+
+    /// Leave ribs inside a hollow wing, at this spacing along the span
+    #[arg(long, requires = "wall")]
+    ribs: Option<f64>,
+
+    /// Thickness of the ribs [default: the skin thickness]
+    #[arg(long)]
+    rib_thickness: Option<f64>,
+
+    /// Cut lightening holes through the ribs
+    #[arg(long, value_enum, requires = "ribs")]
+    lightening_holes: Option<rib::Hole>,
+
+    /// Number of lightening holes in each rib
+    #[arg(long, default_value_t = 3)]
+    lightening_hole_count: usize,
+
+    /// Size of the lightening holes, as a fraction of the space between the skins
+    #[arg(long, default_value_t = 0.6)]
+    lightening_hole_size: f64,
+
+    /// Cut a channel for a spar, given as `shape,position[,start[,end]][,straight]`.
+    /// The shape is `round:DIAMETER` or `rect:WIDTHxHEIGHT`, the position is
+    /// along the chord as in `--sweep-reference`, and the spar runs from the
+    /// root to the tip unless given a start and end span. Spars follow the
+    /// sweep and dihedral of the wing, or run in a straight line if `straight`
+    /// is given. May be repeated
+    #[arg(long)]
+    spar: Vec<spar::Spar>,
+
+    /// Cut a servo bay into the wing, given as
+    /// `span,position,WIDTHxLENGTHxDEPTH[,top|bottom]`. The bay is centred on
+    /// the span and the position along the chord, with its width along the
+    /// chord and its depth into the wing from the `bottom` (the default) or
+    /// `top` skin. Each bay has a channel for its wires running to the root,
+    /// joined up with the other bay's across the root of a fused wing. May be
+    /// repeated
+    #[arg(long)]
+    servo_bay: Vec<servo::ServoBay>,
+
+    /// Diameter of the channels for servo wires
+    #[arg(long, default_value_t = 5.)]
+    servo_wire_diameter: f64,
+
+    /// Split a control surface from the trailing edge, given as
+    /// `inboard,outboard[,start[,end[,gap]]][,hinge]`. The hinge line is at
+    /// the `inboard` and `outboard` chord fractions at the ends of the
+    /// surface, which runs from the root to the tip unless given a start and
+    /// end span. The gap around the surface defaults to 1, and the hinge is
+    /// `top` (the default), `center` or `beveled`. May be repeated
+    #[arg(long, group = "surfaces")]
+    control_surface: Vec<control::ControlSurface>,
+
+    /// Split a rudder from the trailing edge of a fin, given as for
+    /// --control-surface but with a `beveled` hinge unless given another
+    #[arg(long, group = "surfaces", requires = "fin", value_parser = control::parse_rudder)]
+    rudder: Option<control::ControlSurface>,
+
+    /// Write the control surfaces to this file, instead of with the wing
+    #[arg(long, requires = "surfaces")]
+    control_surface_outfile: Option<String>,
+
+    /// Split the wing into segments no longer than this along the span, each
+    /// written to its own numbered file. The cuts are kept clear of ribs,
+    /// servo bays and the ends of spars
+    #[arg(long)]
+    max_segment_length: Option<f64>,
+
+    /// How the segments of a split wing are aligned where they join
+    #[arg(long, value_enum, default_value_t = segment::Joiner::Pins)]
+    joiner: segment::Joiner,
+
+    /// Diameter of the joiner pins, or width of the dovetail keys at the
+    /// joint [default: from the thickness of the section]
+    #[arg(long, requires = "max_segment_length")]
+    joiner_size: Option<f64>,
+
+    /// Which wing to generate. The left wing extends along +Z from the root
+    /// and the right wing is its mirror image along -Z
+    #[arg(long, value_enum, default_value_t = wing::Side::Left)]
+    side: wing::Side,
+
+    /// With --side both, join the wings into one solid instead of two separate ones
+    #[arg(long)]
+    fuse: bool,
+
+    /// Make a vertical fin instead of a wing, standing up along +Y from its
+    /// root
+    #[arg(long, conflicts_with_all = ["side", "fuse"])]
+    fin: bool,
 
     /// Where to write the stl-formatted model
     #[arg(short, long)]
     outfile: String,
 
-    /// Selig-formatted airfoil data
-    file: String,
+    /// Generate a NACA 4, 5, 6 or 16-series airfoil instead of reading a file
+    /// (e.g. naca2412, naca23012, naca65-415, naca16-212)
+    #[arg(long, group = "airfoil")]
+    naca: Option<String>,
+
+    /// Generate a CST (Kulfan) airfoil from these upper surface coefficients
+    #[arg(
+        long,
+        group = "airfoil",
+        requires = "cst_lower",
+        value_delimiter = ',',
+        allow_hyphen_values = true
+    )]
+    cst_upper: Option<Vec<f64>>,
+
+    /// Lower surface coefficients of a CST airfoil
+    #[arg(
+        long,
+        requires = "cst_upper",
+        value_delimiter = ',',
+        allow_hyphen_values = true
+    )]
+    cst_lower: Option<Vec<f64>>,
+
+    /// Leading edge class exponent of a CST airfoil
+    #[arg(long, default_value_t = 0.5)]
+    cst_n1: f64,
+
+    /// Trailing edge class exponent of a CST airfoil
+    #[arg(long, default_value_t = 1.0)]
+    cst_n2: f64,
+
+    /// Trailing edge thickness of a CST airfoil, as a fraction of chord
+    #[arg(long, default_value_t = 0.0)]
+    cst_te_thickness: f64,
+
+    /// Mean line 'a' parameter for generated 6 and 16-series airfoils
+    #[arg(long, default_value_t = 1.0)]
+    mean_line: f64,
+
+    /// Number of points per surface for generated airfoils, and when repaneling
+    /// sections with different airfoils
+    #[arg(long, default_value_t = 80)]
+    points: usize,
+
+    /// Close the trailing edge of generated airfoils
+    #[arg(long)]
+    closed_te: bool,
+
+    /// Move the leading edge to the origin, level the chord line and scale to unit chord
+    #[arg(long)]
+    normalize: bool,
+
+    /// Resample the airfoil to this many points per surface
+    #[arg(long)]
+    repanel: Option<usize>,
+
+    /// Point distribution along each surface when repaneling
+    #[arg(long, value_enum, default_value_t = airfoil::Spacing::Cosine)]
+    spacing: airfoil::Spacing,
+
+    /// Minimum trailing edge thickness, in model units
+    #[arg(long)]
+    te_thickness: Option<f64>,
+
+    /// Fraction of the chord over which the trailing edge is thickened
+    #[arg(long, default_value_t = 0.2, requires = "te_thickness")]
+    te_blend: f64,
+
+    /// Shape of a thickened trailing edge
+    #[arg(long, value_enum, default_value_t = airfoil::TrailingEdge::Blunt, requires = "te_thickness")]
+    te_cap: airfoil::TrailingEdge,
+
+    /// Fit a smooth spline through the airfoil points instead of straight segments
+    #[arg(long)]
+    smooth: bool,
+
+    /// Selig or Lednicer-formatted airfoil data
+    #[arg(group = "airfoil")]
+    file: Option<String>,
 }
 
 fn wire_from_path(path: BezPath, verts: &mut HashMap<(u64, u64), Vertex>) -> Wire {
     let mut vert = |p: kurbo::Point| {
         let (x, y) = (p.x, p.y);
 
-        let k = (x.to_bits(), y.to_bits());
+        // Adding zero turns -0.0 into 0.0, so both map to the same vertex.
+        let k = ((x + 0.).to_bits(), (y + 0.).to_bits());
         if let Some(v) = verts.get(&k) {
             v.clone()
         } else {
@@ -85,15 +377,24 @@ fn wire_from_path(path: BezPath, verts: &mut HashMap<(u64, u64), Vertex>) -> Wir
     edges.into()
 }
 
-fn solid_to_stl(s: Solid, tolerance: f64) -> Vec<u8> {
+/// Tessellates `solids` into a binary STL. The seams left by booleans are
+/// closed where `cut` says any were applied.
+fn solid_to_stl(solids: Vec<Solid>, tolerance: f64, cut: bool) -> Vec<u8> {
     use truck_meshalgo::tessellation::MeshableShape;
     use truck_meshalgo::tessellation::MeshedShape;
-    let mut mesh = s.triangulation(tolerance).to_polygon();
+    let mut mesh = truck_polymesh::PolygonMesh::default();
+    for s in solids {
+        mesh.merge(s.triangulation(tolerance).to_polygon());
+    }
 
     use truck_meshalgo::filters::OptimizingFilter;
     mesh.put_together_same_attrs()
         .remove_degenerate_faces()
         .remove_unused_attrs();
+    let mesh = match cut {
+        true => close_seams(&mesh),
+        false => mesh,
+    };
 
     let mut out = Vec::with_capacity(1024);
     truck_polymesh::stl::write(&mesh, &mut out, truck_polymesh::stl::STLType::Binary).unwrap();
@@ -101,73 +402,894 @@ fn solid_to_stl(s: Solid, tolerance: f64) -> Vec<u8> {
     out
 }
 
+/// How far apart vertices can be and still be taken as the same point when
+/// closing the seams of a mesh, as a fraction of the size of the mesh.
+const SEAM_TOLERANCE: f64 = 1e-6;
+
+/// Closes the seams left in a mesh where the faces either side of an edge
+/// were tessellated differently, as happens along the thin slivers of skin
+/// left by a cut running close to the edge of a face.
+///
+/// Vertices closer together than `SEAM_TOLERANCE` of the size of the mesh
+/// are welded, triangles with no area are dropped, and a triangle with the
+/// vertices of its neighbours lying along an open edge is split at them, so
+/// every edge is shared by two triangles.
+fn close_seams(mesh: &truck_polymesh::PolygonMesh) -> truck_polymesh::PolygonMesh {
+    let positions = mesh.positions();
+    let tolerance = SEAM_TOLERANCE * mesh.bounding_box().diameter();
+    let cell = |p: &Point3| {
+        let key = |c: f64| (c / tolerance).floor() as i64;
+        (key(p.x), key(p.y), key(p.z))
+    };
+    let mut cells: HashMap<(i64, i64, i64), Vec<usize>> = HashMap::new();
+    let welded: Vec<usize> = positions
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let (x, y, z) = cell(p);
+            let neighbours = (-1..=1).flat_map(|dx| {
+                (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (x + dx, y + dy, z + dz)))
+            });
+            let same = neighbours
+                .filter_map(|key| cells.get(&key))
+                .flatten()
+                .find(|&&j| positions[j].distance(*p) < tolerance)
+                .copied();
+            same.unwrap_or_else(|| {
+                cells.entry((x, y, z)).or_default().push(i);
+                i
+            })
+        })
+        .collect();
+    let mut triangles: Vec<[usize; 3]> = mesh
+        .faces()
+        .triangle_iter()
+        .map(|t| t.map(|v| welded[v.pos]))
+        .filter(|t| {
+            // Triangles with all their corners in a line have no area, and
+            // only leave extra edges along the seam.
+            let [a, b, c] = t.map(|i| positions[i]);
+            let longest = [a.distance(b), b.distance(c), c.distance(a)]
+                .into_iter()
+                .fold(0., f64::max);
+            (b - a).cross(c - a).magnitude() > tolerance * longest
+        })
+        .collect();
+
+    // Splitting a triangle can leave another seam along one of its new
+    // edges, so this goes on until there are none left to close.
+    loop {
+        let edges: HashSet<(usize, usize)> = triangles
+            .iter()
+            .flat_map(|t| [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])])
+            .collect();
+        let open: Vec<usize> = edges
+            .iter()
+            .filter(|&&(a, b)| !edges.contains(&(b, a)))
+            .flat_map(|&(a, b)| [a, b])
+            .collect();
+        let mut split = false;
+        triangles = triangles
+            .into_iter()
+            .flat_map(|t| {
+                for k in 0..3 {
+                    let (a, b, c) = (t[k], t[(k + 1) % 3], t[(k + 2) % 3]);
+                    if edges.contains(&(b, a)) {
+                        continue;
+                    }
+                    let (pa, pb) = (positions[a], positions[b]);
+                    let along = pb - pa;
+                    let mut between: Vec<(f64, usize)> = open
+                        .iter()
+                        .filter(|&&v| v != a && v != b)
+                        .filter_map(|&v| {
+                            let s = (positions[v] - pa).dot(along) / along.magnitude2();
+                            let off = (pa + along * s).distance(positions[v]);
+                            (s > 0. && s < 1. && off < tolerance).then_some((s, v))
+                        })
+                        .collect();
+                    if between.is_empty() {
+                        continue;
+                    }
+                    between.sort_by(|x, y| x.0.total_cmp(&y.0));
+                    between.dedup_by_key(|x| x.1);
+                    split = true;
+                    let mut run = vec![a];
+                    run.extend(between.into_iter().map(|(_, v)| v));
+                    run.push(b);
+                    return run.windows(2).map(|pair| [pair[0], pair[1], c]).collect();
+                }
+                vec![t]
+            })
+            .collect();
+        if !split {
+            break;
+        }
+    }
+
+    let attributes = truck_polymesh::StandardAttributes {
+        positions: positions.to_vec(),
+        ..Default::default()
+    };
+    let mut mesh = truck_polymesh::PolygonMesh::new(attributes, triangles.iter().collect());
+    use truck_meshalgo::filters::OptimizingFilter;
+    mesh.remove_unused_attrs();
+    mesh
+}
+
+/// Loads the main airfoil given on the command line.
+fn main_airfoil(args: &Args) -> Vec<kurbo::Point> {
+    if let Some(designation) = &args.naca {
+        naca_airfoil(designation, args)
+    } else if let (Some(upper), Some(lower)) = (&args.cst_upper, &args.cst_lower) {
+        cst::Cst {
+            upper: upper.clone(),
+            lower: lower.clone(),
+            n1: args.cst_n1,
+            n2: args.cst_n2,
+            te_thickness: args.cst_te_thickness,
+        }
+        .contour(args.points)
+    } else {
+        file_airfoil(args.file.as_ref().unwrap())
+    }
+}
+
+/// Loads the airfoil of a station, which is either the path to a coordinate
+/// file or a NACA designation such as `naca2412`.
+fn station_airfoil(spec: &str, args: &Args) -> Vec<kurbo::Point> {
+    if !std::path::Path::new(spec).exists() && spec.to_ascii_lowercase().starts_with("naca") {
+        naca_airfoil(spec, args)
+    } else {
+        file_airfoil(spec)
+    }
+}
+
+fn naca_airfoil(designation: &str, args: &Args) -> Vec<kurbo::Point> {
+    if designation.contains('-') {
+        naca6::generate(designation, args.points, args.mean_line)
+    } else {
+        naca::generate(designation, args.points, args.closed_te)
+    }
+}
+
+fn file_airfoil(path: &str) -> Vec<kurbo::Point> {
+    let reader = BufReader::new(File::open(path).expect("failed opening file"));
+    airfoil::read_coordinates(reader)
+}
+
 fn main() {
     let args = Args::parse();
-    let reader = BufReader::new(File::open(args.file).expect("failed opening file"));
-
-    // Build a kurbo path from the airfoil data.
-    let mut path = BezPath::from_vec(vec![
-        PathEl::MoveTo((1., 0.).into()), // trailing edge
-    ]);
-    for (i, line) in reader.lines().enumerate() {
-        if i < 2 {
-            continue;
-        }
-        let line = line.unwrap();
-        let line = line.trim_start().trim_end();
-        if line.len() == 0 || line.starts_with("#") {
-            continue;
-        }
-
-        let numbers: Vec<_> = line
-            .split_terminator(&[' ', '\t'][..])
-            .filter_map(|s| {
-                if s == "" {
-                    return None;
+    let sections = Sections::new(&args);
+    let wings = sections.wings(&args);
+    let cuts = cuts_all(&args, &wings);
+    let parts = wings
+        .iter()
+        .map(|wing| pieces(&args, wing, &cuts))
+        .collect();
+    write_all(&args, parts);
+}
+
+/// Builds a path through the points of a profile, smoothed if asked for.
+fn path_from_points(points: &[kurbo::Point], args: &Args) -> BezPath {
+    if args.smooth {
+        spline::smooth_path(points)
+    } else {
+        airfoil::path_from_points(points)
+    }
+}
+
+/// Lofts a solid through `profiles` placed at `stations`.
+fn loft(stations: &[wing::Station], profiles: &[Vec<kurbo::Point>], args: &Args) -> Solid {
+    let sections: Vec<Wire> = stations
+        .iter()
+        .zip(profiles)
+        .map(|(station, points)| {
+            let profile = wire_from_path(path_from_points(points, args), &mut HashMap::new());
+            builder::transformed(&profile, station.transform(args.twist_axis))
+        })
+        .collect();
+    wing::loft(&sections)
+}
+
+/// Writes `solids` to an STL file at `path`, where `cut` says whether any
+/// booleans were applied to them.
+fn write_stl(path: &str, solids: Vec<Solid>, cut: bool, args: &Args) {
+    // The wing is built along +Z, so a fin is stood up from its root.
+    let solids = match args.fin {
+        true => solids
+            .iter()
+            .map(|solid| builder::transformed(solid, Matrix4::from_angle_x(Deg(-90.))))
+            .collect(),
+        false => solids,
+    };
+    use std::io::Write;
+    let mut f = File::create(path).expect("Unable to create file");
+    f.write_all(&solid_to_stl(solids, 0.05, cut)).unwrap();
+}
+
+/// The sections a wing is lofted through, from the root to the tip.
+struct Sections {
+    stations: Vec<wing::Station>,
+    profiles: Vec<Vec<kurbo::Point>>,
+    /// How many of the sections are of the wing itself, before any winglet
+    /// and tip cap.
+    wing_sections: usize,
+    /// How many of the sections are of the tip cap, at the end.
+    cap_sections: usize,
+}
+
+impl Sections {
+    /// The sections of the wing given on the command line.
+    fn new(args: &Args) -> Sections {
+        let chord_table = match &args.chord_table {
+            Some(path) => wing::read_chord_table(path),
+            None => Vec::new(),
+        };
+
+        let mut stations: Vec<wing::Station> = if !args.station.is_empty() {
+            args.station.clone()
+        } else if let Some(path) = &args.stations {
+            wing::read_stations(path)
+        } else {
+            let (span, root_chord, tip_chord) = match chord_table.last() {
+                Some(&(span, tip_chord)) => (span, chord_table[0].1, tip_chord),
+                None => (
+                    args.semi_wingspan.unwrap(),
+                    args.root_chord.unwrap(),
+                    args.tip_chord.unwrap(),
+                ),
+            };
+            let sweep = match args.sweep_angle {
+                Some(angle) => {
+                    wing::swept_le_offset(angle, args.sweep_reference, span, root_chord, tip_chord)
+                }
+                None => args.sweep,
+            };
+            vec![
+                wing::Station {
+                    span: 0.,
+                    chord: root_chord,
+                    le_offset: 0.,
+                    height: 0.,
+                    twist: 0.,
+                    dihedral: 0.,
+                    roll: 0.,
+                    airfoil: None,
+                },
+                wing::Station {
+                    span,
+                    chord: tip_chord,
+                    le_offset: sweep,
+                    height: 0.,
+                    twist: args.tip_twist,
+                    dihedral: args.dihedral,
+                    roll: 0.,
+                    airfoil: args.tip_airfoil.clone(),
+                },
+            ]
+        };
+        if stations.windows(2).any(|w| w[1].span <= w[0].span) {
+            panic!("stations must be given in order of increasing span");
+        }
+        wing::apply_dihedral(&mut stations);
+
+        // The main airfoil is only needed for stations which don't give their own.
+        let main_airfoil_given =
+            args.naca.is_some() || args.cst_upper.is_some() || args.file.is_some();
+        if !main_airfoil_given && stations.iter().any(|s| s.airfoil.is_none()) {
+            Args::command()
+                .error(
+                    clap::error::ErrorKind::MissingRequiredArgument,
+                    "an airfoil is needed with --naca, --cst-upper or FILE, unless every station gives its own",
+                )
+                .exit();
+        }
+
+        // Sections with different airfoils must have the same number of points
+        // to be lofted together, so they are always repaneled.
+        let mixed_airfoils = stations.iter().any(|s| s.airfoil != stations[0].airfoil)
+            || args.winglet_airfoil.is_some();
+        let repanel = args.repanel.or(mixed_airfoils.then_some(args.points));
+
+        let mut airfoils: HashMap<Option<String>, Vec<kurbo::Point>> = HashMap::new();
+        let mut load_airfoil = |airfoil: &Option<String>| {
+            let points = airfoils.entry(airfoil.clone()).or_insert_with(|| {
+                // Build a kurbo path from the airfoil data.
+                let points = match airfoil {
+                    Some(spec) => station_airfoil(spec, args),
+                    None => main_airfoil(args),
                 };
-                let f: Option<f64> = match s.parse() {
-                    Ok(v) => Some(v),
-                    Err(_) => None,
+                let points = if args.normalize {
+                    let (points, normalization) = airfoil::normalize(&points);
+                    eprintln!("normalized airfoil: {}", normalization);
+                    points
+                } else {
+                    points
                 };
-                f
-            })
+                match repanel {
+                    Some(n) => airfoil::repanel(&path_from_points(&points, args), n, args.spacing),
+                    None => points,
+                }
+            });
+            points.clone()
+        };
+        let mut profiles: Vec<Vec<kurbo::Point>> =
+            stations.iter().map(|s| load_airfoil(&s.airfoil)).collect();
+
+        // Curved planforms are followed with intermediate sections between the
+        // root and tip, with their airfoils blended by span.
+        let chords = if let Some(&(span, _)) = chord_table.last() {
+            let table = chord_table.iter().map(|&(s, chord)| (s / span, chord));
+            Some(table.collect::<Vec<_>>())
+        } else if args.planform != wing::Planform::Linear {
+            let (root, tip) = (&stations[0], &stations[1]);
+            Some(args.planform.chords(root.chord, tip.chord, args.sections))
+        } else {
+            None
+        };
+        if let Some(chords) = chords {
+            let (root, tip) = (&stations[0], &stations[1]);
+            let (root_profile, tip_profile) = (&profiles[0], &profiles[1]);
+            profiles = chords
+                .iter()
+                .map(|&(eta, _)| airfoil::lerp(root_profile, tip_profile, eta))
+                .collect();
+            stations = wing::distribute(root, tip, &chords, args.sweep_reference);
+        }
+
+        let wing_sections = stations.len();
+        if let Some(height) = args.winglet_height {
+            let winglet = wing::Winglet {
+                height,
+                cant: args.winglet_cant,
+                toe: args.winglet_toe,
+                sweep: args.winglet_sweep,
+                taper: args.winglet_taper,
+                radius: args.winglet_radius,
+            };
+            let profile = match &args.winglet_airfoil {
+                Some(_) => load_airfoil(&args.winglet_airfoil),
+                None => profiles.last().unwrap().clone(),
+            };
+            let tip = (stations.last().unwrap(), profiles.last().unwrap());
+            let (sections, section_profiles): (Vec<_>, Vec<_>) =
+                winglet.sections(tip.0, tip.1, &profile).into_iter().unzip();
+            stations.extend(sections);
+            profiles.extend(section_profiles);
+        }
+
+        // The trailing edge thickness is in model units, so it differs
+        // between sections of different chord.
+        if let Some(t) = args.te_thickness {
+            for (station, profile) in stations.iter().zip(profiles.iter_mut()) {
+                *profile =
+                    airfoil::thicken_te(profile, t / station.chord, args.te_blend, args.te_cap);
+            }
+        }
+
+        // A closed trailing edge has one less edge than an open one, so mixed
+        // sections are all closed to loft them together.
+        let is_closed = |p: &Vec<kurbo::Point>| p[0] == p[p.len() - 1];
+        if profiles.iter().any(is_closed) && !profiles.iter().all(is_closed) {
+            for profile in profiles.iter_mut().filter(|p| !is_closed(p)) {
+                *profile = airfoil::close_te(profile, args.te_blend);
+            }
+        }
+
+        let tip = (stations.last().unwrap(), profiles.last().unwrap());
+        let (cap, cap_profiles): (Vec<wing::Station>, Vec<_>) = args
+            .tip_cap
+            .sections(tip.0, tip.1, args.tip_cap_length)
+            .into_iter()
+            .unzip();
+        let cap_sections = cap.len();
+        stations.extend(cap);
+        profiles.extend(cap_profiles);
+
+        Sections {
+            stations,
+            profiles,
+            wing_sections,
+            cap_sections,
+        }
+    }
+
+    /// The sections of the wing itself, leaving out any winglet and tip cap.
+    fn main(&self) -> (&[wing::Station], &[Vec<kurbo::Point>]) {
+        (
+            &self.stations[..self.wing_sections],
+            &self.profiles[..self.wing_sections],
+        )
+    }
+
+    /// The wings to make of the sections: one side, or both either fused
+    /// into one or kept apart.
+    fn wings(&self, args: &Args) -> Vec<Wing> {
+        // Features are only worked out for the wings being made, as there may
+        // not be room to cut the others into segments.
+        let (stations, profiles) = self.main();
+        let capped = self.stations.len() > self.wing_sections;
+        let features = |side: wing::Side| Features::new(args, stations, profiles, capped, side);
+        let mirrored = wing::mirrored(&self.stations);
+        let mirrored_profiles: Vec<_> = self.profiles.iter().rev().cloned().collect();
+        let (n, c) = (self.stations.len(), self.cap_sections);
+        let left = || Wing {
+            stations: self.stations.clone(),
+            profiles: self.profiles.clone(),
+            inside: 0..n - c,
+            features: features(wing::Side::Left),
+        };
+        let right = || Wing {
+            stations: mirrored.clone(),
+            profiles: mirrored_profiles.clone(),
+            inside: c..n,
+            features: features(wing::Side::Right),
+        };
+        match args.side {
+            wing::Side::Left => vec![left()],
+            wing::Side::Right => vec![right()],
+            wing::Side::Both if args.fuse => {
+                // Both halves share the root section, so there are no internal faces.
+                let stations = [&mirrored[..n - 1], &self.stations[..]].concat();
+                let profiles = [&mirrored_profiles[..n - 1], &self.profiles[..]].concat();
+                let inside = c..stations.len() - c;
+                vec![Wing {
+                    stations,
+                    profiles,
+                    inside,
+                    features: features(wing::Side::Both),
+                }]
+            }
+            wing::Side::Both => vec![left(), right()],
+        }
+    }
+}
+
+/// A wing to loft and cut: one side, or both sides fused into one.
+struct Wing {
+    stations: Vec<wing::Station>,
+    profiles: Vec<Vec<kurbo::Point>>,
+    /// Sections the inside of a hollow wing is lofted through, which leaves
+    /// out the tip caps.
+    inside: std::ops::Range<usize>,
+    features: Features,
+}
+
+/// What's cut from the solid lofted for a wing.
+struct Features {
+    /// Spans left solid inside a hollow wing, for ribs, servo bays and
+    /// joints.
+    solid: Vec<(f64, f64)>,
+    /// Spar channels, lightening holes and servo bays.
+    holes: Vec<Solid>,
+    /// Tools cutting control surfaces out with the gap around them, and the
+    /// holes for joiners.
+    cutouts: Vec<Solid>,
+    /// Tools taking the control surfaces from the wing.
+    surfaces: Vec<Solid>,
+    /// Tools taking the dovetail keys from the wing.
+    keys: Vec<Solid>,
+    /// Spans at which the wing is split into segments.
+    segments: Vec<f64>,
+}
+
+impl Features {
+    /// The features of one side of the wing made of `stations`, or of both
+    /// sides fused into one for `Side::Both`. When `capped`, a winglet or tip
+    /// cap carries on past the last station.
+    fn new(
+        args: &Args,
+        stations: &[wing::Station],
+        profiles: &[Vec<kurbo::Point>],
+        capped: bool,
+        side: wing::Side,
+    ) -> Features {
+        // Ribs and servo bays are left solid inside a hollow wing, with the
+        // skin carried round the ends of the bays.
+        let ribs = ribs(args, stations);
+        let wall = args.wall.unwrap_or_default();
+        let solid = [
+            rib::solid_spans(&ribs, rib_thickness(args)),
+            servo::solid_spans(&args.servo_bay, wall),
+        ]
+        .concat();
+
+        // Long wings are split into segments at spans clear of the ribs,
+        // servo bays and the ends of spars, with joiners across each cut.
+        let joint = |span: f64| {
+            let (joiner, size) = (args.joiner, args.joiner_size);
+            segment::joint(joiner, size, stations, profiles, span, args.twist_axis)
+        };
+        let (joiner, size) = (args.joiner, args.joiner_size);
+        let reach = segment::reach(joiner, size, stations, profiles, args.twist_axis, wall);
+        let segments = match args.max_segment_length {
+            Some(length) => {
+                let (root, tip) = (stations[0].span, stations[stations.len() - 1].span);
+                let mut avoid = solid.clone();
+                for spar in &args.spar {
+                    let ends = [spar.start, spar.end].into_iter().flatten();
+                    let ends = ends.filter(|&end| end > root && end < tip);
+                    let half = spar.shape.size();
+                    avoid.extend(ends.map(|end| (end - half, end + half)));
+                }
+                segment::cuts(stations, &avoid, length, reach, side)
+            }
+            None => vec![],
+        };
+        let mut solid = match side {
+            wing::Side::Left => solid,
+            wing::Side::Right => segment::mirrored(&solid),
+            wing::Side::Both => [segment::mirrored(&solid), solid].concat(),
+        };
+
+        // Hollow wings are left solid around the joiners.
+        let joints: Vec<_> = segments.iter().map(|&span| joint(span)).collect();
+        solid.extend(segments.iter().map(|cut| (cut - reach, cut + reach)));
+        solid.sort_by(|a, b| a.0.total_cmp(&b.0));
+        // Overlapping spans are merged, so the cavities fall between them.
+        let solid = solid.into_iter().fold(vec![], |mut spans, (from, to)| {
+            match spans.last_mut() {
+                Some((_, end)) if from <= *end => *end = end.max(to),
+                _ => spans.push((from, to)),
+            }
+            spans
+        });
+
+        let mut holes = spar::channels(&spars(args, stations, profiles), side);
+        // The holes through each rib are cut out together, joining up the
+        // cavities either side. Cuts into larger cavities are slower, so the
+        // ribs are cut every other one first, then every other one of those
+        // left, and so on, which keeps the cavities being joined small.
+        let mut rib_holes: Vec<_> = rib_holes(args, stations, profiles, &ribs)
+            .into_iter()
+            .enumerate()
+            .collect();
+        rib_holes.sort_by_key(|(i, _)| (i + 1).trailing_zeros());
+        let left = rib_holes
+            .iter()
+            .map(|(_, holes)| wing::join(holes.iter().map(|hole| hole.solid())));
+        let right = rib_holes
+            .iter()
+            .map(|(_, holes)| wing::join(holes.iter().map(|hole| hole.mirrored().solid())));
+        holes.extend(side.select(left, right));
+        let pockets: Vec<_> = args
+            .servo_bay
+            .iter()
+            .map(|bay| bay.pocket(stations, profiles, args.twist_axis))
             .collect();
+        let left = pockets.iter().map(|pocket| pocket.solid());
+        let right = pockets.iter().map(|pocket| pocket.mirrored().solid());
+        holes.extend(side.select(left, right));
 
-        if numbers.len() != 2 {
-            panic!(
-                "expected 2 decimal datapoints per line, got {} on line {}",
-                numbers.len(),
-                i
+        // Control surfaces are split off with one tool taking the surface
+        // from the wing and another cutting it out of the wing with the gap
+        // around it.
+        let surfaces: Vec<_> = args
+            .control_surface
+            .iter()
+            .chain(&args.rudder)
+            .cloned()
+            .collect();
+        let tools = |part| {
+            let twist_axis = args.twist_axis;
+            control::tools(
+                &surfaces, part, stations, profiles, twist_axis, capped, side,
+            )
+        };
+        let mut cutouts = tools(control::Part::Cutout);
+        cutouts.extend(joints.iter().flat_map(|joint| joint.holes()));
+        Features {
+            solid,
+            holes,
+            cutouts,
+            surfaces: tools(control::Part::Surface),
+            keys: joints.iter().flat_map(|joint| joint.keys()).collect(),
+            segments,
+        }
+    }
+}
+
+/// Thickness of the ribs, which defaults to the thickness of the skin.
+fn rib_thickness(args: &Args) -> f64 {
+    args.rib_thickness.or(args.wall).unwrap_or_default()
+}
+
+/// Spans of the ribs inside the hollow wing made of `stations`.
+fn ribs(args: &Args, stations: &[wing::Station]) -> Vec<f64> {
+    match (args.ribs, args.wall) {
+        (Some(spacing), Some(_)) => {
+            let (root, tip) = (&stations[0], &stations[stations.len() - 1]);
+            rib::spans(spacing, root.span, tip.span, rib_thickness(args))
+        }
+        _ => vec![],
+    }
+}
+
+/// The lightening holes through each of the `ribs` in the wing made of
+/// `stations`.
+fn rib_holes(
+    args: &Args,
+    stations: &[wing::Station],
+    profiles: &[Vec<kurbo::Point>],
+    ribs: &[f64],
+) -> Vec<Vec<rib::RibHole>> {
+    let (Some(hole), Some(wall)) = (args.lightening_holes, args.wall) else {
+        return vec![];
+    };
+    // Lightening holes go through the rib into the cavities either side.
+    let depth = rib_thickness(args) + 2. * wall;
+    ribs.iter()
+        .map(|&span| {
+            let (station, profile) = wing::section_at(stations, profiles, span);
+            let cavity = airfoil::offset(&profile, wall / station.chord);
+            let outlines = hole.outlines(
+                &cavity,
+                args.lightening_hole_count,
+                args.lightening_hole_size,
             );
+            outlines
+                .iter()
+                .map(|outline| rib::RibHole::place(&station, outline, depth, args.twist_axis))
+                .collect()
+        })
+        .collect()
+}
+
+/// The spars along the wing made of `stations`, with their paths. Servo bays
+/// each have a channel for their wires running back to the root like a
+/// spar.
+fn spars(
+    args: &Args,
+    stations: &[wing::Station],
+    profiles: &[Vec<kurbo::Point>],
+) -> Vec<(spar::Spar, spar::Path)> {
+    let wires = args.servo_bay.iter().map(|bay| {
+        bay.wire(
+            args.servo_wire_diameter,
+            stations,
+            profiles,
+            args.twist_axis,
+        )
+    });
+    args.spar
+        .iter()
+        .map(|spar| (spar.clone(), spar.path(stations, profiles, args.twist_axis)))
+        .chain(wires)
+        .collect()
+}
+
+/// Spans at which the `wings` being made are split into separate files: the
+/// cuts between the segments of each wing, and the root between unfused
+/// halves, which are kept in separate segments.
+fn cuts_all(args: &Args, wings: &[Wing]) -> Vec<f64> {
+    if args.max_segment_length.is_none() {
+        return vec![];
+    }
+    let mut cuts: Vec<f64> = wings
+        .iter()
+        .flat_map(|wing| wing.features.segments.iter().copied())
+        .collect();
+    if wings.len() > 1 {
+        cuts.push(wings[0].stations[0].span);
+    }
+    cuts.sort_by(f64::total_cmp);
+    cuts
+}
+
+/// The inside of the hollow `wing`, lofted through the inside of the skin
+/// `wall` thick in pieces between the spans left solid.
+fn cavities(args: &Args, wing: &Wing, wall: f64) -> Vec<Solid> {
+    let (stations, profiles) = (&wing.stations, &wing.profiles);
+    let solid = &wing.features.solid;
+    wing::cavities(stations, profiles, wing.inside.clone(), solid, wall)
+        .into_iter()
+        .map(|(stations, profiles)| {
+            let profiles: Vec<_> = profiles
+                .iter()
+                .map(|inner| {
+                    let path = path_from_points(inner, args);
+                    airfoil::repanel(&path, args.points, args.spacing)
+                })
+                .collect();
+            loft(&stations, &profiles, args)
+        })
+        .collect()
+}
+
+/// A segment of a wing, written to a file of its own.
+#[derive(Default)]
+struct Piece {
+    /// The segment, with any other elements of the wing.
+    solids: Vec<Solid>,
+    /// The control surfaces split from the segment.
+    surfaces: Vec<Solid>,
+    /// Whether the segment was cut with any booleans.
+    cut: bool,
+}
+
+/// Each segment of `wing` between the sorted `cuts`, and the keys for its
+/// joints. The wing is split before anything is cut from it, which keeps the
+/// cuts clear of the faces of the split.
+fn pieces(args: &Args, wing: &Wing, cuts: &[f64]) -> (Vec<Piece>, Vec<Solid>) {
+    let features = &wing.features;
+    // The elements of the sections are lofted separately, with the features
+    // only cut from the main element. The other elements are lofted piece
+    // by piece, rather than cut from the whole.
+    let (slat, flap) = (args.slat.as_ref(), args.slotted_flap.as_ref());
+    let mut elements = element::split_all(&wing.profiles, slat, flap).into_iter();
+    let solid = loft(&wing.stations, &elements.next().unwrap(), args);
+    let ends = [&[f64::NEG_INFINITY][..], cuts, &[f64::INFINITY]].concat();
+    let elements: Vec<Vec<_>> = elements
+        .map(|profiles| {
+            ends.windows(2)
+                .map(|pair| {
+                    segment::sections(&wing.stations, &profiles, pair[0], pair[1])
+                        .map(|(stations, profiles)| loft(&stations, &profiles, args))
+                })
+                .collect()
+        })
+        .collect();
+
+    let solid = match args.wall {
+        Some(wall) => cavities(args, wing, wall)
+            .into_iter()
+            .fold(solid, wing::hollow),
+        None => solid,
+    };
+    let cut = !cuts.is_empty()
+        || !features.holes.is_empty()
+        || !features.surfaces.is_empty()
+        || !features.cutouts.is_empty();
+    let keys = features
+        .keys
+        .iter()
+        .map(|tool| segment::key(&solid, tool, &features.holes))
+        .collect();
+    let pieces = segment::split(&solid, cuts)
+        .into_iter()
+        .enumerate()
+        .map(|(i, piece)| {
+            let mut piece = piece
+                .map(|solid| {
+                    let solid = features
+                        .holes
+                        .iter()
+                        .fold(solid, |solid, hole| wing::subtract(&solid, hole));
+                    let (solid, surfaces) =
+                        control::split_all(solid, &features.surfaces, &features.cutouts);
+                    Piece {
+                        solids: vec![solid],
+                        surfaces,
+                        cut,
+                    }
+                })
+                .unwrap_or_default();
+            piece
+                .solids
+                .extend(elements.iter().filter_map(|pieces| pieces[i].clone()));
+            piece
+        })
+        .collect();
+    (pieces, keys)
+}
+
+/// Writes the pieces of each wing made, each with its keys, to the files
+/// given on the command line. The pieces of the wings in the same segment
+/// go in one file, numbered when the wings are split into segments.
+fn write_all(args: &Args, parts: Vec<(Vec<Piece>, Vec<Solid>)>) {
+    let mut files: Vec<Piece> = vec![];
+    let mut keys = vec![];
+    for (pieces, part_keys) in parts {
+        files.resize_with(pieces.len(), Piece::default);
+        for (file, piece) in files.iter_mut().zip(pieces) {
+            file.solids.extend(piece.solids);
+            file.surfaces.extend(piece.surfaces);
+            file.cut |= piece.cut;
         }
+        keys.extend(part_keys);
+    }
 
-        path.push(PathEl::LineTo((numbers[0], numbers[1]).into()));
+    let numbered = files.len() > 1;
+    let name = |path: &str, i: usize| match numbered {
+        true => segment::suffixed(path, &(i + 1).to_string()),
+        false => path.to_string(),
+    };
+    for (i, mut file) in files.into_iter().enumerate() {
+        match &args.control_surface_outfile {
+            Some(outfile) if !file.surfaces.is_empty() => {
+                write_stl(&name(outfile, i), file.surfaces, file.cut, args)
+            }
+            Some(_) => {}
+            None => file.solids.extend(file.surfaces),
+        }
+        if !file.solids.is_empty() {
+            write_stl(&name(&args.outfile, i), file.solids, file.cut, args);
+        }
+    }
+    if !keys.is_empty() {
+        write_stl(&segment::suffixed(&args.outfile, "keys"), keys, true, args);
     }
+}
 
-    let scale_mat = Matrix4::from_nonuniform_scale(
-        args.tip_chord / args.root_chord,
-        args.tip_chord / args.root_chord,
-        1.,
-    );
-    let translate_mat = Matrix4::from_translation(args.sweep * Vector3::unit_x())
-        + Matrix4::from_translation(args.semi_wingspan * Vector3::unit_z());
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let profile = builder::scaled(
-        &wire_from_path(path, &mut HashMap::new()),
-        Point3::new(0., 0., 0.),
-        Vector3::new(args.root_chord, args.root_chord, args.root_chord),
-    );
-    let bottom: Wire = profile.clone();
-    let top = builder::transformed(&profile, scale_mat + translate_mat);
+    #[test]
+    fn slotted_flaps_replace_control_surfaces() {
+        let args = "airfoil-to-stl -o wing.stl -w 100 -r 50 -t 50 --naca 2412 --slotted-flap 0.3";
+        let args: Vec<_> = args.split_whitespace().collect();
+        assert!(Args::try_parse_from(&args).is_ok());
+        let surface = ["--control-surface", "0.7,0.7"];
+        assert!(Args::try_parse_from(args.iter().chain(&surface)).is_err());
+    }
 
-    let mut base: Shell = builder::try_wire_homotopy(&bottom, &top).unwrap();
+    #[test]
+    fn negative_cst_coefficients() {
+        // Lists of negative coefficients can follow the option with or
+        // without an `=`.
+        let forms: [&[&str]; 2] = [
+            &["--cst-upper", "0.2,-0.1", "--cst-lower", "-0.1,-0.1"],
+            &["--cst-upper=0.2,-0.1", "--cst-lower=-0.1,-0.1"],
+        ];
+        for cst in forms {
+            let args = "airfoil-to-stl -o wing.stl -w 100 -r 50 -t 50".split_whitespace();
+            let args = Args::try_parse_from(args.chain(cst.iter().copied())).unwrap();
+            assert_eq!(args.cst_upper, Some(vec![0.2, -0.1]));
+            assert_eq!(args.cst_lower, Some(vec![-0.1, -0.1]));
+        }
+    }
 
-    // Inverted bc opposite faces must have opposite normals
-    base.push(builder::try_attach_plane(&[bottom]).unwrap().inverse());
-    base.push(builder::try_attach_plane(&[top]).unwrap());
+    /// The edges of `mesh` with no edge running the other way beside them.
+    fn open_edges(mesh: &truck_polymesh::PolygonMesh) -> Vec<(Point3, Point3)> {
+        let positions = mesh.positions();
+        let edges: Vec<(usize, usize)> = mesh
+            .faces()
+            .triangle_iter()
+            .flat_map(|t| {
+                [
+                    (t[0].pos, t[1].pos),
+                    (t[1].pos, t[2].pos),
+                    (t[2].pos, t[0].pos),
+                ]
+            })
+            .collect();
+        edges
+            .iter()
+            .filter(|&&(a, b)| !edges.contains(&(b, a)))
+            .map(|&(a, b)| (positions[a], positions[b]))
+            .collect()
+    }
 
-    let solid = Solid::new(vec![base]);
-    let mut f = File::create(args.outfile).expect("Unable to create file");
-    use std::io::Write;
-    f.write(&solid_to_stl(solid, 0.05)).unwrap();
+    #[test]
+    fn close_seams_at_a_t_junction() {
+        // A tetrahedron with its base split at the middle of one edge, which
+        // the side over that edge runs straight past. The sides have their
+        // own corners, a little apart from those of the base.
+        for scale in [1e-3, 1., 1e3] {
+            let nudge = Vector3::new(1., 1., 1.) * SEAM_TOLERANCE * 0.1;
+            let (a, b, c) = (
+                Point3::origin(),
+                Point3::new(1., 0., 0.),
+                Point3::new(0., 1., 0.),
+            );
+            let d = Point3::new(0., 0., 1.);
+            let m = b.midpoint(c);
+            let positions = [a, b, c, m, a + nudge, b + nudge, c + nudge, d]
+                .map(|p| Point3::from_vec(p.to_vec() * scale))
+                .to_vec();
+            let triangles = [[0, 2, 3], [0, 3, 1], [4, 5, 7], [5, 6, 7], [6, 4, 7]];
+            let attributes = truck_polymesh::StandardAttributes {
+                positions,
+                ..Default::default()
+            };
+            let mesh = truck_polymesh::PolygonMesh::new(attributes, triangles.iter().collect());
+            assert_eq!(open_edges(&mesh).len(), 7, "scale {}", scale);
+
+            let closed = close_seams(&mesh);
+            assert_eq!(open_edges(&closed), vec![], "scale {}", scale);
+            assert_eq!(closed.faces().triangle_iter().count(), 6, "scale {}", scale);
+        }
+    }
 }
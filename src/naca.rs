@@ -0,0 +1,229 @@
+use crate::airfoil::cosine_spacing;
+use kurbo::Point;
+
+/// A mean line, returning the camber and its slope at a chord position.
+pub type MeanLine = Box<dyn Fn(f64) -> (f64, f64)>;
+
+/// Generates the contour of a NACA 4-digit (e.g. `naca2412`) or 5-digit
+/// (e.g. `naca23012`) airfoil, using the standard thickness and camber
+/// equations.
+///
+/// Each surface is sampled at `points` intervals with cosine spacing, and the
+/// returned points are in Selig order. If `closed_te` is set, the thickness
+/// equation is modified to close the trailing edge.
+pub fn generate(designation: &str, points: usize, closed_te: bool) -> Vec<Point> {
+    let digits = designation
+        .trim()
+        .trim_start_matches(|c: char| c.is_ascii_alphabetic() || c.is_whitespace());
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        panic!("invalid NACA designation: {:?}", designation);
+    }
+    let digit = |i: usize| (digits.as_bytes()[i] - b'0') as f64;
+
+    let (camber, thickness) = match digits.len() {
+        4 => (
+            four_digit_camber(digit(0) / 100., digit(1) / 10.),
+            digits[2..].parse::<f64>().unwrap() / 100.,
+        ),
+        5 if digit(2) > 1. => panic!(
+            "the third digit of a NACA 5-digit airfoil must be 0, or 1 for a reflexed mean line, got {:?}",
+            designation
+        ),
+        5 => (
+            five_digit_camber(3. * digit(0) / 20., digit(1) as usize, digit(2) == 1.),
+            digits[3..].parse::<f64>().unwrap() / 100.,
+        ),
+        _ => panic!(
+            "only 4 and 5-digit NACA airfoils are supported, got {:?}",
+            designation
+        ),
+    };
+
+    let te_coeff = if closed_te { -0.1036 } else { -0.1015 };
+    let half_thickness = |x: f64| {
+        5. * thickness
+            * (0.2969 * x.sqrt() - 0.1260 * x - 0.3516 * x.powi(2)
+                + 0.2843 * x.powi(3)
+                + te_coeff * x.powi(4))
+    };
+
+    let mut contour = contour(points, half_thickness, camber);
+    if closed_te {
+        // Avoid a sliver of an edge from rounding error at the trailing edge.
+        let last = contour.len() - 1;
+        contour[last] = contour[0];
+    }
+    contour
+}
+
+/// Combines a thickness distribution and a mean line into a contour with
+/// cosine-spaced points, applying the thickness perpendicular to the mean line.
+pub fn contour(points: usize, half_thickness: impl Fn(f64) -> f64, camber: MeanLine) -> Vec<Point> {
+    let xs = cosine_spacing(points.max(2));
+
+    let surface = |x: f64, side: f64| {
+        let (yc, dyc) = camber(x);
+        let yt = half_thickness(x);
+        let theta = dyc.atan();
+        Point::new(x - side * yt * theta.sin(), yc + side * yt * theta.cos())
+    };
+
+    let upper = xs.iter().rev().map(|&x| surface(x, 1.));
+    let lower = xs.iter().skip(1).map(|&x| surface(x, -1.));
+    upper.chain(lower).collect()
+}
+
+/// NACA 4-digit mean line with maximum camber `m` at chord position `p`.
+fn four_digit_camber(m: f64, p: f64) -> MeanLine {
+    Box::new(move |x| {
+        if m == 0. || p == 0. {
+            (0., 0.)
+        } else if x < p {
+            (
+                m / p.powi(2) * (2. * p * x - x.powi(2)),
+                2. * m / p.powi(2) * (p - x),
+            )
+        } else {
+            (
+                m / (1. - p).powi(2) * (1. - 2. * p + 2. * p * x - x.powi(2)),
+                2. * m / (1. - p).powi(2) * (p - x),
+            )
+        }
+    })
+}
+
+/// NACA 5-digit mean line with design lift coefficient `cl`, maximum camber
+/// position index `p` (position = p/20), optionally reflexed.
+// 0.318 is a tabulated coefficient, not an approximation of 1/π.
+#[allow(clippy::approx_constant)]
+fn five_digit_camber(cl: f64, p: usize, reflex: bool) -> MeanLine {
+    // Tabulated for a design lift coefficient of 0.3: (r, k1, k2/k1).
+    let (r, k1, k21) = match (p, reflex) {
+        (1, false) => (0.0580, 361.4, 0.),
+        (2, false) => (0.1260, 51.64, 0.),
+        (3, false) => (0.2025, 15.957, 0.),
+        (4, false) => (0.2900, 6.643, 0.),
+        (5, false) => (0.3910, 3.230, 0.),
+        (2, true) => (0.1300, 51.990, 0.000764),
+        (3, true) => (0.2170, 15.793, 0.00677),
+        (4, true) => (0.3180, 6.520, 0.0303),
+        (5, true) => (0.4410, 3.191, 0.1355),
+        _ => panic!(
+            "unsupported NACA 5-digit mean line: p={} reflex={}",
+            p, reflex
+        ),
+    };
+    let k1 = k1 * cl / 0.3;
+
+    Box::new(move |x| {
+        if !reflex {
+            if x < r {
+                (
+                    k1 / 6. * (x.powi(3) - 3. * r * x.powi(2) + r.powi(2) * (3. - r) * x),
+                    k1 / 6. * (3. * x.powi(2) - 6. * r * x + r.powi(2) * (3. - r)),
+                )
+            } else {
+                (k1 * r.powi(3) / 6. * (1. - x), -k1 * r.powi(3) / 6.)
+            }
+        } else {
+            let base = -k21 * (1. - r).powi(3) * x - r.powi(3) * x + r.powi(3);
+            let dbase = -k21 * (1. - r).powi(3) - r.powi(3);
+            if x < r {
+                (
+                    k1 / 6. * ((x - r).powi(3) + base),
+                    k1 / 6. * (3. * (x - r).powi(2) + dbase),
+                )
+            } else {
+                (
+                    k1 / 6. * (k21 * (x - r).powi(3) + base),
+                    k1 / 6. * (3. * k21 * (x - r).powi(2) + dbase),
+                )
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The upper surface ordinate at `x` of a symmetric contour, which is
+    /// sampled there.
+    fn upper_at(points: &[Point], x: f64) -> f64 {
+        points
+            .iter()
+            .find(|p| (p.x - x).abs() < 1e-12 && p.y > 0.)
+            .expect("a point at x")
+            .y
+    }
+
+    #[test]
+    fn naca0012_ordinates() {
+        // Abbott & von Doenhoff, Theory of Wing Sections, appendix I. With
+        // 60 cosine-spaced points, there are points at x = 0.25 and 0.5.
+        let points = generate("naca0012", 60, false);
+        assert!((upper_at(&points, 0.25) - 0.05941).abs() < 5e-5);
+        assert!((upper_at(&points, 0.5) - 0.05294).abs() < 5e-5);
+        // The trailing edge is left 0.252% thick.
+        assert!(points[0].distance(Point::new(1., 0.00126)) < 1e-9);
+    }
+
+    #[test]
+    fn closed_trailing_edge() {
+        let points = generate("0012", 60, true);
+        assert_eq!(points[0], points[points.len() - 1]);
+        assert!(points[0].y.abs() < 1e-6);
+    }
+
+    #[test]
+    fn four_digit_mean_line() {
+        // NACA 2412: 2% camber at 40% of the chord.
+        let camber = four_digit_camber(0.02, 0.4);
+        let (y, slope) = camber(0.4);
+        assert!((y - 0.02).abs() < 1e-12);
+        assert!(slope.abs() < 1e-12);
+        assert_eq!(camber(0.).0, 0.);
+        assert!(camber(1.).0.abs() < 1e-12);
+    }
+
+    #[test]
+    fn five_digit_mean_line() {
+        // NACA 230: 1.84% camber at 15% of the chord.
+        let camber = five_digit_camber(0.3, 3, false);
+        let (y, slope) = camber(0.15);
+        assert!((y - 0.0184).abs() < 5e-5);
+        assert!(slope.abs() < 1e-3);
+        assert!(camber(1.).0.abs() < 1e-12);
+
+        // The reflexed mean line comes back to the chord at the trailing edge.
+        let reflexed = five_digit_camber(0.3, 3, true);
+        assert!(reflexed(1.).0.abs() < 1e-9);
+        assert!(reflexed(0.).0.abs() < 1e-12);
+    }
+
+    #[test]
+    fn five_digit_contour() {
+        // The thickness is laid either side of the mean line, so the middle
+        // of the surfaces at each sampled point is on it.
+        let points = generate("naca23012", 60, false);
+        let camber = five_digit_camber(0.3, 3, false);
+        let n = 60;
+        for i in 1..n {
+            let (upper, lower) = (points[n - i], points[n + i]);
+            let middle = upper.midpoint(lower);
+            assert!((middle.y - camber(middle.x).0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "third digit of a NACA 5-digit airfoil")]
+    fn rejects_a_five_digit_third_digit_past_one() {
+        generate("naca23512", 40, false);
+    }
+
+    #[test]
+    #[should_panic(expected = "only 4 and 5-digit")]
+    fn rejects_other_lengths() {
+        generate("naca123", 40, false);
+    }
+}
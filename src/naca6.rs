@@ -0,0 +1,217 @@
+use crate::naca::{contour, MeanLine};
+use crate::spline::natural_spline;
+use kurbo::Point;
+use std::f64::consts::PI;
+
+/// Chord stations (in percent) of the tabulated 6-series thickness forms.
+const STATIONS: [f64; 26] = [
+    0., 0.5, 0.75, 1.25, 2.5, 5., 7.5, 10., 15., 20., 25., 30., 35., 40., 45., 50., 55., 60., 65.,
+    70., 75., 80., 85., 90., 95., 100.,
+];
+
+/// Half-thickness (in percent of chord) of the 63-010, 64-010 and 65-010 basic
+/// thickness forms, from Abbott & von Doenhoff.
+const THICKNESS_63: [f64; 26] = [
+    0., 0.829, 1.004, 1.275, 1.756, 2.440, 2.950, 3.362, 3.994, 4.445, 4.753, 4.938, 5.000, 4.938,
+    4.766, 4.496, 4.140, 3.715, 3.234, 2.712, 2.166, 1.618, 1.088, 0.604, 0.214, 0.,
+];
+const THICKNESS_64: [f64; 26] = [
+    0., 0.804, 0.969, 1.225, 1.688, 2.327, 2.805, 3.199, 3.813, 4.272, 4.606, 4.837, 4.968, 4.995,
+    4.894, 4.684, 4.388, 4.021, 3.597, 3.127, 2.623, 2.103, 1.582, 1.062, 0.541, 0.,
+];
+const THICKNESS_65: [f64; 26] = [
+    0., 0.772, 0.932, 1.169, 1.574, 2.177, 2.647, 3.040, 3.666, 4.143, 4.503, 4.760, 4.924, 4.996,
+    4.963, 4.812, 4.530, 4.146, 3.682, 3.156, 2.584, 1.987, 1.385, 0.810, 0.306, 0.,
+];
+
+/// Generates the contour of a NACA 6-series (e.g. `63-412`, `65(2)-415`) or
+/// 16-series (e.g. `16-212`) airfoil.
+///
+/// The first digit after the dash is the design lift coefficient in tenths,
+/// and the remaining two the thickness in percent of chord. The camber is
+/// produced by an a-series mean line with the given `a` (1.0 for uniform
+/// loading along the whole chord).
+///
+/// 6-series thickness forms are interpolated from the tabulated 10% thick
+/// sections and scaled to the requested thickness.
+pub fn generate(designation: &str, points: usize, a: f64) -> Vec<Point> {
+    let invalid = || panic!("invalid NACA 6 or 16-series designation: {:?}", designation);
+
+    let designation = designation
+        .trim()
+        .trim_start_matches(|c: char| c.is_ascii_alphabetic() || c.is_whitespace());
+    let (series, digits) = designation.split_once('-').unwrap_or_else(invalid);
+    // Any low-drag range subscript after the series, such as 65(2)- or 65_2-,
+    // doesn't change the section shape.
+    if series.len() < 2 || !series.is_char_boundary(2) {
+        invalid();
+    }
+    let series = &series[..2];
+    if digits.len() != 3 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        invalid();
+    }
+
+    let cl = (digits.as_bytes()[0] - b'0') as f64 / 10.;
+    let thickness = digits[1..].parse::<f64>().unwrap() / 100.;
+
+    let half_thickness: Box<dyn Fn(f64) -> f64> = match series {
+        "16" => Box::new(move |x: f64| thickness * sixteen_series_thickness(x)),
+        "63" => tabulated_thickness(&THICKNESS_63, thickness),
+        "64" => tabulated_thickness(&THICKNESS_64, thickness),
+        "65" => tabulated_thickness(&THICKNESS_65, thickness),
+        _ => panic!(
+            "unsupported NACA series {:?}, expected 16, 63, 64 or 65",
+            series
+        ),
+    };
+
+    let mut contour = contour(points, half_thickness, mean_line(a, cl));
+    if series != "16" {
+        // The tabulated forms have a closed trailing edge.
+        let last = contour.len() - 1;
+        contour[last] = contour[0];
+    }
+    contour
+}
+
+/// Thickness distribution of the 16-series, normalized to a thickness of 1.
+fn sixteen_series_thickness(x: f64) -> f64 {
+    if x < 0.5 {
+        0.989665 * x.sqrt() - 0.23925 * x - 0.041 * x.powi(2) - 0.5594 * x.powi(3)
+    } else {
+        let x = 1. - x;
+        0.01 + 2.325 * x - 3.42 * x.powi(2) + 1.46 * x.powi(3)
+    }
+}
+
+/// Interpolates a tabulated 10% thick form with a natural cubic spline in
+/// `sqrt(x)`, which keeps the round leading edge smooth.
+fn tabulated_thickness(table: &'static [f64; 26], thickness: f64) -> Box<dyn Fn(f64) -> f64> {
+    let u: Vec<f64> = STATIONS.iter().map(|x| (x / 100.).sqrt()).collect();
+    let y: Vec<f64> = table.iter().map(|y| y / 100. * thickness / 0.1).collect();
+    let m = natural_spline(&u, &y);
+
+    Box::new(move |x: f64| {
+        let x = x.clamp(0., 1.).sqrt();
+        let i = u.partition_point(|&u| u <= x).clamp(1, u.len() - 1) - 1;
+        let h = u[i + 1] - u[i];
+        let (a, b) = ((u[i + 1] - x) / h, (x - u[i]) / h);
+        a * y[i]
+            + b * y[i + 1]
+            + ((a.powi(3) - a) * m[i] + (b.powi(3) - b) * m[i + 1]) * h.powi(2) / 6.
+    })
+}
+
+/// NACA a-series mean line, with uniform loading from the leading edge to
+/// chord position `a` and a linear decrease to zero at the trailing edge.
+pub fn mean_line(a: f64, cl: f64) -> MeanLine {
+    if a <= 0. || a > 1. {
+        panic!("mean line parameter a must be in (0, 1], got {}", a);
+    }
+    // x*ln(x), which goes to zero at x = 0.
+    let xlnx = |x: f64| if x <= 0. { 0. } else { x * x.ln() };
+    // Keep the slope finite at the ends and at x = a.
+    let clamp = |x: f64| x.clamp(1e-9, 1. - 1e-9);
+
+    if a >= 1. {
+        return Box::new(move |x| {
+            let x = x.clamp(0., 1.);
+            let y = -cl / (4. * PI) * (xlnx(1. - x) + xlnx(x));
+            let x = clamp(x);
+            (y, cl / (4. * PI) * ((1. - x) / x).ln())
+        });
+    }
+
+    let g = -1. / (1. - a) * (a.powi(2) * (a.ln() / 2. - 0.25) + 0.25);
+    let h = 1. / (1. - a) * ((1. - a).powi(2) * (1. - a).ln() / 2. - (1. - a).powi(2) / 4.) + g;
+    let k = cl / (2. * PI * (a + 1.));
+
+    Box::new(move |x| {
+        let x = x.clamp(0., 1.);
+        let (d, e) = ((a - x).abs(), 1. - x);
+        let y = k
+            * (1. / (1. - a)
+                * (0.5 * d * xlnx(d) - 0.5 * e * xlnx(e) + 0.25 * e.powi(2) - 0.25 * d.powi(2))
+                - xlnx(x)
+                + g
+                - h * x);
+
+        let x = clamp(x);
+        let (d, e) = (a - x, 1. - x);
+        let d = if d.abs() < 1e-9 { 1e-9 } else { d };
+        let dy = k * (1. / (1. - a) * (e * e.ln() - d * d.abs().ln()) - x.ln() - 1. - h);
+        (y, dy)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tabulated_forms_pass_through_the_table() {
+        for (table, i) in [
+            (&THICKNESS_63, 12),
+            (&THICKNESS_64, 13),
+            (&THICKNESS_65, 13),
+        ] {
+            let half_thickness = tabulated_thickness(table, 0.1);
+            for (x, y) in STATIONS.iter().zip(table) {
+                assert!((half_thickness(x / 100.) - y / 100.).abs() < 1e-12);
+            }
+            // Each is thickest around where the table says.
+            let thickest = (0..=1000)
+                .map(|x| x as f64 / 1000.)
+                .max_by(|&a, &b| half_thickness(a).total_cmp(&half_thickness(b)))
+                .unwrap();
+            assert!(thickest > STATIONS[i - 1] / 100. && thickest < STATIONS[i + 1] / 100.);
+        }
+    }
+
+    #[test]
+    fn sixteen_series_thickness_form() {
+        // Thickest at half the chord, with a trailing edge 1% of the
+        // thickness from the trailing edge term.
+        assert!((sixteen_series_thickness(0.5) - 0.5).abs() < 1e-4);
+        assert!((sixteen_series_thickness(1.) - 0.01).abs() < 1e-12);
+        assert_eq!(sixteen_series_thickness(0.), 0.);
+    }
+
+    #[test]
+    fn uniform_load_mean_line() {
+        // Abbott & von Doenhoff: the a = 1.0 mean line for a design lift
+        // coefficient of 1 is 5.516% of the chord high at its middle.
+        let (y, slope) = mean_line(1., 1.)(0.5);
+        assert!((y - 0.05516).abs() < 5e-6);
+        assert!(slope.abs() < 1e-12);
+    }
+
+    #[test]
+    fn mean_line_ends_on_the_chord() {
+        for a in [0.5, 0.8, 1.] {
+            let camber = mean_line(a, 0.4);
+            assert!(camber(0.).0.abs() < 1e-12);
+            assert!(camber(1.).0.abs() < 1e-9, "a = {}", a);
+            assert!(camber(0.5).0 > 0.);
+        }
+    }
+
+    #[test]
+    fn sections() {
+        // NACA 64-012 is 12% thick and symmetric, with a closed trailing
+        // edge; 64(2)-415 names the same section as 64-415.
+        let points = generate("naca64-012", 80, 1.);
+        assert!((crate::airfoil::thickness(&points) - 0.12).abs() < 1e-3);
+        assert_eq!(points[0], points[points.len() - 1]);
+        assert_eq!(generate("64(2)-415", 40, 0.8), generate("64-415", 40, 0.8));
+
+        let points = generate("16-012", 80, 1.);
+        assert!((crate::airfoil::thickness(&points) - 0.12).abs() < 1e-3);
+    }
+
+    #[test]
+    #[should_panic(expected = "unsupported NACA series")]
+    fn rejects_other_series() {
+        generate("66-012", 40, 1.);
+    }
+}
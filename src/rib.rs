@@ -0,0 +1,122 @@
+use crate::airfoil;
+use crate::wing;
+use kurbo::Point;
+use truck_modeling::{Point3, Solid};
+
+/// Shape of the lightening holes cut through ribs.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Hole {
+    /// Row of round holes along the chord
+    #[value(alias = "circular")]
+    Circle,
+    /// Alternating triangles, leaving a truss between the skins
+    #[value(alias = "triangular")]
+    Triangle,
+}
+
+/// Chordwise range of the section the holes are spread over, clear of the
+/// leading edge and the thin trailing edge.
+const HOLE_RANGE: (f64, f64) = (0.12, 0.75);
+
+/// Spans of ribs placed every `spacing` from the root, stopping short of the
+/// tip by at least `thickness`.
+pub fn spans(spacing: f64, root: f64, tip: f64, thickness: f64) -> Vec<f64> {
+    if spacing <= 0. {
+        panic!("rib spacing must be positive, got {}", spacing);
+    }
+    (1..)
+        .map(|i| root + spacing * i as f64)
+        .take_while(|&span| span < tip - thickness)
+        .collect()
+}
+
+/// Spans taken up by ribs `thickness` thick at the spans `ribs`, which are
+/// left solid inside a hollow wing.
+pub fn solid_spans(ribs: &[f64], thickness: f64) -> Vec<(f64, f64)> {
+    let half = thickness / 2.;
+    ribs.iter().map(|rib| (rib - half, rib + half)).collect()
+}
+
+impl Hole {
+    /// Outlines of `count` holes in a unit-chord section whose inside is
+    /// `cavity`, each taking up `size` of the space between the skins.
+    pub fn outlines(&self, cavity: &[Point], count: usize, size: f64) -> Vec<Vec<Point>> {
+        let end = cavity.iter().map(|p| p.x).fold(0., f64::max);
+        let (start, end) = (HOLE_RANGE.0, HOLE_RANGE.1.min(end));
+        let pitch = (end - start) / count as f64;
+
+        (0..count)
+            .map(|i| {
+                let x = start + pitch * (i as f64 + 0.5);
+                let (upper, lower) = airfoil::surfaces_at(cavity, x);
+                let center = Point::new(x, (upper + lower) / 2.);
+                let (width, height) = (pitch * size, (upper - lower) * size);
+                match self {
+                    Hole::Circle => {
+                        const SEGMENTS: usize = 24;
+                        let r = width.min(height) / 2.;
+                        (0..SEGMENTS)
+                            .map(|i| {
+                                let angle = std::f64::consts::TAU * i as f64 / SEGMENTS as f64;
+                                center + kurbo::Vec2::new(angle.cos(), angle.sin()) * r
+                            })
+                            .collect()
+                    }
+                    Hole::Triangle => {
+                        let (w, h) = (width / 2., height / 2.);
+                        let corners = if i % 2 == 0 {
+                            [(-w, -h), (w, -h), (0., h)]
+                        } else {
+                            [(-w, h), (0., -h), (w, h)]
+                        };
+                        corners
+                            .iter()
+                            .map(|&(x, y)| center + kurbo::Vec2::new(x, y))
+                            .collect()
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// A hole through a rib, as its outline placed in the wing.
+#[derive(Debug, Clone)]
+pub struct RibHole {
+    /// Corners of the outline, in the plane of the rib.
+    pub outline: Vec<Point3>,
+    /// Depth of the cut through the rib, centred on the outline.
+    pub depth: f64,
+}
+
+impl RibHole {
+    /// Places a hole outline given for the unit-chord section of `station`.
+    pub fn place(
+        station: &wing::Station,
+        outline: &[Point],
+        depth: f64,
+        twist_axis: f64,
+    ) -> RibHole {
+        let transform = station.transform(twist_axis);
+        let outline = outline
+            .iter()
+            .map(|p| {
+                Point3::from_homogeneous(transform * Point3::new(p.x, p.y, 0.).to_homogeneous())
+            })
+            .collect();
+        RibHole { outline, depth }
+    }
+
+    /// The same hole in the mirrored wing.
+    pub fn mirrored(&self) -> RibHole {
+        RibHole {
+            outline: wing::mirror_points(&self.outline),
+            depth: self.depth,
+        }
+    }
+
+    /// The solid filling the hole.
+    pub fn solid(&self) -> Solid {
+        wing::extrude(&self.outline, self.depth)
+    }
+}
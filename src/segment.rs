@@ -0,0 +1,429 @@
+use crate::airfoil;
+use crate::spar;
+use crate::wing::{self, Side, Station};
+use kurbo::Point;
+use truck_modeling::{BoundingBox, Point3, Solid, Vector3};
+
+/// How neighbouring segments of a split wing are aligned where they join.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Joiner {
+    /// Holes for round pins, such as dowels or lengths of filament, across
+    /// the joint.
+    Pins,
+    /// A bow-tie slot through the wing across the joint, with a matching key
+    /// to print and glue in.
+    Dovetail,
+}
+
+/// Chordwise positions of the alignment pins.
+const PIN_POSITIONS: [f64; 2] = [0.2, 0.45];
+
+/// Chordwise position of the dovetail key.
+const KEY_POSITION: f64 = 0.3;
+
+/// Gap left around a dovetail key so it fits into its slot.
+const KEY_CLEARANCE: f64 = 0.15;
+
+/// Spans at which to cut a wing running from `root` to `tip` into segments
+/// no longer than `max_length`, with none of the cuts falling inside the
+/// spans in `avoid`.
+///
+/// The segments are kept about the same length, only moving cuts as far as
+/// needed to clear the spans to avoid.
+pub fn spans(root: f64, tip: f64, max_length: f64, avoid: &[(f64, f64)]) -> Vec<f64> {
+    if max_length <= 0. {
+        panic!("segment length must be positive, got {}", max_length);
+    }
+    let clear = |span: f64| avoid.iter().all(|&(from, to)| span <= from || span >= to);
+
+    let mut cuts = vec![];
+    let mut from = root;
+    while tip - from > max_length {
+        let remaining = ((tip - from) / max_length).ceil();
+        let target = from + (tip - from) / remaining;
+        let candidates = avoid.iter().flat_map(|&(a, b)| [a, b]);
+        let cut = std::iter::once(target)
+            .chain(candidates)
+            .filter(|&span| span > from && span <= from + max_length && clear(span))
+            .min_by(|a, b| (a - target).abs().total_cmp(&(b - target).abs()))
+            .unwrap_or_else(|| {
+                panic!(
+                    "no room to cut the wing between {} and {}, clear of ribs and spars",
+                    from,
+                    from + max_length
+                )
+            });
+        cuts.push(cut);
+        from = cut;
+    }
+    cuts
+}
+
+/// Spans at which one side of the wing made of `stations` is cut into
+/// segments no longer than `length`, or both sides fused into one for
+/// `Side::Both`. The cuts are kept `reach` clear of the spans to `avoid` in
+/// the side as defined, such as ribs, servo bays and the ends of spars.
+pub fn cuts(
+    stations: &[Station],
+    avoid: &[(f64, f64)],
+    length: f64,
+    reach: f64,
+    side: Side,
+) -> Vec<f64> {
+    let (root, tip) = (stations[0].span, stations[stations.len() - 1].span);
+    let mut avoid: Vec<(f64, f64)> = avoid
+        .iter()
+        .map(|&(from, to)| (from - reach, to + reach))
+        .collect();
+    // Cuts are kept off the sections, where they'd run along the edges of
+    // the lofted faces.
+    avoid.extend(
+        stations
+            .iter()
+            .map(|s| (s.span - reach / 4., s.span + reach / 4.)),
+    );
+
+    let between = |from: f64, to: f64, avoid: &[(f64, f64)]| {
+        let ends = [
+            (f64::NEG_INFINITY, from + 2. * reach),
+            (to - 2. * reach, f64::INFINITY),
+        ];
+        spans(from, to, length, &[avoid, &ends].concat())
+    };
+    match side {
+        Side::Left => between(root, tip, &avoid),
+        Side::Right => {
+            let cuts = between(root, tip, &avoid);
+            cuts.iter().rev().map(|span| -span).collect()
+        }
+        Side::Both => {
+            // The halves meet at an angle at the root, which cuts are kept
+            // well clear of.
+            let root = [(-root - 2. * reach, root + 2. * reach)];
+            let avoid = [&mirrored(&avoid)[..], &root, &avoid].concat();
+            between(-tip, tip, &avoid)
+        }
+    }
+}
+
+/// The same spans in the mirrored wing.
+pub fn mirrored(spans: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    spans.iter().rev().map(|&(from, to)| (-to, -from)).collect()
+}
+
+/// The `joiner` across a cut at `span` through the wing made of `stations`,
+/// with negative spans in the mirrored wing. The joiners are sized to the
+/// section if not given a `size`.
+pub fn joint(
+    joiner: Joiner,
+    size: Option<f64>,
+    stations: &[Station],
+    profiles: &[Vec<Point>],
+    span: f64,
+    twist_axis: f64,
+) -> Joint {
+    let (station, profile) = wing::section_at(stations, profiles, span.abs());
+    let joint = Joint::place(joiner, &station, &profile, size, twist_axis);
+    match span < 0. {
+        true => joint.mirrored(),
+        false => joint,
+    }
+}
+
+/// How far either side of a cut through the wing made of `stations` it's
+/// left solid around the joiners, inside a skin `wall` thick.
+pub fn reach(
+    joiner: Joiner,
+    size: Option<f64>,
+    stations: &[Station],
+    profiles: &[Vec<Point>],
+    twist_axis: f64,
+    wall: f64,
+) -> f64 {
+    // Joiners are sized to the section, so are largest at the root.
+    let root = joint(
+        joiner,
+        size,
+        stations,
+        profiles,
+        stations[0].span,
+        twist_axis,
+    );
+    root.depth() + wall
+}
+
+/// The joiners across one cut through the wing.
+#[derive(Debug, Clone)]
+pub struct Joint {
+    pub joiner: Joiner,
+    /// Where the joiners cross the cut, on the mean line of the section.
+    pub centers: Vec<Point3>,
+    /// Diameter of the pins, or width of the dovetail key at the cut.
+    pub size: f64,
+    /// Local chord of the wing, which sets the height of a dovetail slot.
+    pub chord: f64,
+}
+
+impl Joint {
+    /// Places the joiners in the section of `station`, sizing them to the
+    /// thickness of the section if not given a `size`.
+    pub fn place(
+        joiner: Joiner,
+        station: &Station,
+        profile: &[Point],
+        size: Option<f64>,
+        twist_axis: f64,
+    ) -> Joint {
+        let positions = match joiner {
+            Joiner::Pins => &PIN_POSITIONS[..],
+            Joiner::Dovetail => &[KEY_POSITION][..],
+        };
+        let thickness = positions
+            .iter()
+            .map(|&x| {
+                let (upper, lower) = airfoil::surfaces_at(profile, x);
+                (upper - lower) * station.chord
+            })
+            .fold(f64::INFINITY, f64::min);
+        let size = size.unwrap_or(match joiner {
+            Joiner::Pins => thickness * 0.4,
+            Joiner::Dovetail => thickness * 0.8,
+        });
+
+        let transform = station.transform(twist_axis);
+        let centers = positions
+            .iter()
+            .map(|&x| {
+                let y = airfoil::mean_line_at(profile, x);
+                Point3::from_homogeneous(transform * Point3::new(x, y, 0.).to_homogeneous())
+            })
+            .collect();
+        Joint {
+            joiner,
+            centers,
+            size,
+            chord: station.chord,
+        }
+    }
+
+    /// The same joint in the mirrored wing.
+    pub fn mirrored(&self) -> Joint {
+        Joint {
+            centers: wing::mirror_points(&self.centers),
+            ..self.clone()
+        }
+    }
+
+    /// How far the joiners reach into the segments either side of the cut.
+    pub fn depth(&self) -> f64 {
+        match self.joiner {
+            Joiner::Pins => self.size * 2.,
+            Joiner::Dovetail => self.size,
+        }
+    }
+
+    /// The holes for the joiners, to cut out of the wing.
+    pub fn holes(&self) -> Vec<Solid> {
+        self.centers
+            .iter()
+            .map(|&center| match self.joiner {
+                Joiner::Pins => {
+                    let shape = spar::Shape::Round(self.size);
+                    let end = |z: f64| shape.wire(center + Vector3::new(0., 0., z));
+                    wing::loft(&[end(-self.depth()), end(self.depth())])
+                }
+                Joiner::Dovetail => self.key(center, 0.),
+            })
+            .collect()
+    }
+
+    /// The keys which fit into the holes, to take out of the wing before the
+    /// holes are cut. Pins aren't printed, so there are none for them.
+    pub fn keys(&self) -> Vec<Solid> {
+        match self.joiner {
+            Joiner::Pins => vec![],
+            Joiner::Dovetail => self
+                .centers
+                .iter()
+                .map(|&center| self.key(center, KEY_CLEARANCE))
+                .collect(),
+        }
+    }
+
+    /// A bow-tie prism through the wing across the cut at `center`, shrunk
+    /// all round by `clearance`. Its top and bottom are well clear of the
+    /// skins, so cutting it from the wing leaves a key flush with them.
+    fn key(&self, center: Point3, clearance: f64) -> Solid {
+        let (neck, flare, length) = (
+            self.size / 2. - clearance,
+            self.size * 0.75 - clearance,
+            self.depth() - clearance,
+        );
+        // The neck runs straight across the cut, so none of the corners lie
+        // in its plane. Corners are given as (x, z), in order round the
+        // outline.
+        let straight = self.size / 4.;
+        let corners = [
+            (neck, -straight),
+            (flare, -length),
+            (-flare, -length),
+            (-neck, -straight),
+            (-neck, straight),
+            (-flare, length),
+            (flare, length),
+            (neck, straight),
+        ];
+        let end = |y: f64| {
+            let corners: Vec<_> = corners
+                .iter()
+                .map(|&(x, z)| center + Vector3::new(x, y, z))
+                .collect();
+            wing::polygon(&corners)
+        };
+        wing::loft(&[end(-self.chord), end(self.chord)])
+    }
+}
+
+/// Splits `solid` at the planes along Z of each of the sorted `cuts`, giving
+/// the piece before each cut and the piece after the last. Pieces are empty
+/// where the solid doesn't reach between cuts.
+pub fn split(solid: &Solid, cuts: &[f64]) -> Vec<Option<Solid>> {
+    let mut ends = vec![f64::NEG_INFINITY];
+    ends.extend(cuts);
+    ends.push(f64::INFINITY);
+    ends.windows(2)
+        .map(|pair| slice(solid, pair[0], pair[1]))
+        .collect()
+}
+
+/// The piece of `solid` between the planes along Z at `from` and `to`,
+/// which may be infinite to leave that end of the solid whole.
+pub fn slice(solid: &Solid, from: f64, to: f64) -> Option<Solid> {
+    let bbox = bounding_box(solid);
+    let (min, max) = (*bbox.min(), *bbox.max());
+    if from >= max.z || to <= min.z {
+        return None;
+    }
+    if from <= min.z && to >= max.z {
+        return Some(solid.clone());
+    }
+
+    // The piece is taken with a slab between the planes, reaching well past
+    // the solid where it isn't cut.
+    let margin = bbox.diameter();
+    let end = |z: f64| {
+        let (x0, x1) = (min.x - margin, max.x + margin);
+        let (y0, y1) = (min.y - margin, max.y + margin);
+        wing::polygon(&[
+            Point3::new(x0, y0, z),
+            Point3::new(x1, y0, z),
+            Point3::new(x1, y1, z),
+            Point3::new(x0, y1, z),
+        ])
+    };
+    // Where the piece runs to an end of the solid, the slab is carried on
+    // past it, so none of its faces lie on the end faces.
+    let from = if from <= min.z { min.z - margin } else { from };
+    let to = if to >= max.z { max.z + margin } else { to };
+    let slab = wing::loft(&[end(from), end(to)]);
+    Some(wing::intersect(solid, &slab))
+}
+
+/// The sections of a wing through `stations` between the spans `from` and
+/// `to`, which may be infinite to leave that end of the wing whole. Sections
+/// are added at the ends where they fall between stations, so the piece can
+/// be lofted on its own rather than cut from the whole wing.
+pub fn sections(
+    stations: &[Station],
+    profiles: &[Vec<Point>],
+    from: f64,
+    to: f64,
+) -> Option<(Vec<Station>, Vec<Vec<Point>>)> {
+    let (mut sections, mut section_profiles) = (vec![], vec![]);
+    for (i, (a, profile)) in stations.iter().zip(profiles).enumerate() {
+        if a.span >= from && a.span <= to {
+            sections.push(a.clone());
+            section_profiles.push(profile.clone());
+        }
+        let Some(b) = stations.get(i + 1) else {
+            continue;
+        };
+        for end in [from, to] {
+            if a.span < end && end < b.span {
+                let t = (end - a.span) / (b.span - a.span);
+                sections.push(a.lerp(b, t));
+                section_profiles.push(airfoil::lerp(profile, &profiles[i + 1], t));
+            }
+        }
+    }
+    (sections.len() > 1).then_some((sections, section_profiles))
+}
+
+/// Takes the part of `solid` inside a key `tool`, with `holes` cut from it.
+///
+/// The key is taken from a slice of the solid just around it, so it comes
+/// out whole wherever the solid is split.
+pub fn key(solid: &Solid, tool: &Solid, holes: &[Solid]) -> Solid {
+    let bbox = bounding_box(tool);
+    let margin = (bbox.max().z - bbox.min().z) / 4.;
+    let slice = slice(solid, bbox.min().z - margin, bbox.max().z + margin)
+        .expect("dovetail key is clear of the wing");
+    let slice = holes
+        .iter()
+        .fold(slice, |slice, hole| wing::subtract(&slice, hole));
+    wing::intersect(&slice, tool)
+}
+
+/// Bounds of the vertices of `solid`.
+fn bounding_box(solid: &Solid) -> BoundingBox<Point3> {
+    solid
+        .boundaries()
+        .iter()
+        .flat_map(|shell| shell.vertex_iter())
+        .map(|v| v.get_point())
+        .collect()
+}
+
+/// The name of the file written alongside `path` with `suffix` added to
+/// its name, such as the numbered files of a split wing.
+pub fn suffixed(path: &str, suffix: &str) -> String {
+    let path = std::path::Path::new(path);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, suffix, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, suffix),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A box from `(0, 0, z0)` to `(10, 10, z1)`.
+    fn block(z0: f64, z1: f64) -> Solid {
+        let end = |z: f64| {
+            wing::polygon(&[
+                Point3::new(0., 0., z),
+                Point3::new(10., 0., z),
+                Point3::new(10., 10., z),
+                Point3::new(0., 10., z),
+            ])
+        };
+        wing::loft(&[end(z0), end(z1)])
+    }
+
+    #[test]
+    fn slice_from_an_end_face() {
+        // Both halves of an unfused wing are cut at the root, which is the
+        // end face of each of them.
+        let solid = block(0., 100.);
+        let piece = slice(&solid, 0., 40.).expect("piece of the solid");
+        let bbox = bounding_box(&piece);
+        assert_eq!((bbox.min().z, bbox.max().z), (0., 40.));
+
+        let piece = slice(&solid, 60., 100.).expect("piece of the solid");
+        let bbox = bounding_box(&piece);
+        assert_eq!((bbox.min().z, bbox.max().z), (60., 100.));
+    }
+}
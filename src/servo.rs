@@ -0,0 +1,248 @@
+use crate::airfoil;
+use crate::spar::{self, Spar};
+use crate::wing::{self, Station};
+use kurbo::Point;
+use std::str::FromStr;
+use truck_modeling::{Point3, Solid};
+
+/// Which skin a servo bay opens through.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Opening {
+    /// Opens through the upper skin
+    Top,
+    /// Opens through the lower skin
+    Bottom,
+}
+
+/// A pocket for a servo, cut into the wing from one of the skins.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServoBay {
+    /// Span of the middle of the bay.
+    pub span: f64,
+    /// Chordwise position of the middle of the bay, as a fraction of the
+    /// local chord.
+    pub position: f64,
+    /// Size of the bay along the chord.
+    pub width: f64,
+    /// Size of the bay along the span.
+    pub length: f64,
+    /// Depth of the bay into the wing, from the skin at its middle.
+    pub depth: f64,
+    pub opening: Opening,
+}
+
+impl FromStr for ServoBay {
+    type Err = String;
+
+    /// Parses a servo bay given as
+    /// `span,position,WIDTHxLENGTHxDEPTH[,top|bottom]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields: Vec<&str> = s.split(',').map(str::trim).collect();
+        let opening = match fields.last() {
+            Some(f) if f.parse::<f64>().is_err() && !f.contains(['x', 'X']) => {
+                let opening = clap::ValueEnum::from_str(f, true)
+                    .map_err(|_| format!("expected top or bottom opening, got {:?}", f))?;
+                fields.pop();
+                opening
+            }
+            _ => Opening::Bottom,
+        };
+        if fields.len() != 3 {
+            return Err(format!(
+                "expected span,position,WIDTHxLENGTHxDEPTH[,top|bottom], got {:?}",
+                s
+            ));
+        }
+
+        let number = |f: &str| -> Result<f64, String> {
+            f.trim()
+                .parse()
+                .map_err(|_| format!("invalid number {:?} in servo bay {:?}", f, s))
+        };
+        let size: Vec<&str> = fields[2].split(['x', 'X']).collect();
+        if size.len() != 3 {
+            return Err(format!(
+                "expected the size of a servo bay as WIDTHxLENGTHxDEPTH, got {:?}",
+                fields[2]
+            ));
+        }
+
+        Ok(ServoBay {
+            span: number(fields[0])?,
+            position: wing::parse_chord_fraction(fields[1])?,
+            width: number(size[0])?,
+            length: number(size[1])?,
+            depth: number(size[2])?,
+            opening,
+        })
+    }
+}
+
+impl ServoBay {
+    /// The pocket cut for the bay, in the wing made of `stations`.
+    pub fn pocket(&self, stations: &[Station], profiles: &[Vec<Point>], twist_axis: f64) -> Pocket {
+        let (root, tip) = (stations[0].span, stations[stations.len() - 1].span);
+        if self.span - self.length / 2. < root || self.span + self.length / 2. > tip {
+            panic!("servo bay at span {} runs out of the wing", self.span);
+        }
+        let (station, profile) = wing::section_at(stations, profiles, self.span);
+        let transform = station.transform(twist_axis);
+        let place = |x: f64, y: f64| {
+            Point3::from_homogeneous(transform * Point3::new(x, y, 0.).to_homogeneous())
+        };
+        let skin = |x: f64| {
+            let (upper, lower) = airfoil::surfaces_at(&profile, x);
+            match self.opening {
+                Opening::Top => (place(x, upper), place(x, lower)),
+                Opening::Bottom => (place(x, lower), place(x, upper)),
+            }
+        };
+
+        let inwards = self.inwards();
+        let (center, opposite) = skin(self.position);
+        let floor = center.y + inwards * self.depth;
+        if (opposite.y - floor) * inwards <= 0. {
+            panic!(
+                "servo bay at span {} is deeper than the wing is thick",
+                self.span
+            );
+        }
+
+        // The open side is carried on well past the skin anywhere across the
+        // bay, so none of its faces lie on the skin.
+        const SAMPLES: usize = 8;
+        let half_width = self.width / 2. / station.chord;
+        let skins = (0..=SAMPLES).map(|i| {
+            let x = self.position - half_width + 2. * half_width * i as f64 / SAMPLES as f64;
+            skin(x.clamp(0., 1.)).0.y
+        });
+        let outside = match self.opening {
+            Opening::Top => skins.fold(f64::NEG_INFINITY, f64::max) + self.depth,
+            Opening::Bottom => skins.fold(f64::INFINITY, f64::min) - self.depth,
+        };
+
+        let (x0, x1) = (center.x - self.width / 2., center.x + self.width / 2.);
+        let (y0, y1) = match self.opening {
+            Opening::Top => (floor, outside),
+            Opening::Bottom => (outside, floor),
+        };
+        Pocket {
+            outline: vec![
+                Point3::new(x0, y0, center.z),
+                Point3::new(x1, y0, center.z),
+                Point3::new(x1, y1, center.z),
+                Point3::new(x0, y1, center.z),
+            ],
+            length: self.length,
+        }
+    }
+
+    /// The channel for the servo's wires, as a round spar of `diameter` and
+    /// its path from the root into the middle of the bay.
+    pub fn wire(
+        &self,
+        diameter: f64,
+        stations: &[Station],
+        profiles: &[Vec<Point>],
+        twist_axis: f64,
+    ) -> (Spar, spar::Path) {
+        // The channel follows the mean line, clear of the skins, until it's
+        // inside the bay, then turns into the middle of the pocket.
+        let wire = Spar {
+            shape: spar::Shape::Round(diameter),
+            position: self.position,
+            start: None,
+            end: Some(self.span - self.length / 4.),
+            straight: false,
+        };
+        let mut path = wire.path(stations, profiles, twist_axis);
+
+        // The outline runs round from the corner at the start of the chord
+        // and the bottom of the pocket.
+        let outline = self.pocket(stations, profiles, twist_axis).outline;
+        let x = (outline[0].x + outline[1].x) / 2.;
+        let floor = match self.opening {
+            Opening::Top => outline[0].y,
+            Opening::Bottom => outline[2].y,
+        };
+        let y = floor - self.inwards() * self.depth / 2.;
+        path.centers.push(Point3::new(x, y, self.span));
+        (wire, path)
+    }
+
+    /// Which way is into the wing from the opening, along Y.
+    fn inwards(&self) -> f64 {
+        match self.opening {
+            Opening::Top => -1.,
+            Opening::Bottom => 1.,
+        }
+    }
+}
+
+/// Spans left solid around the servo `bays` inside a hollow wing, with the
+/// skin `wall` thick carried round the ends of the bays.
+pub fn solid_spans(bays: &[ServoBay], wall: f64) -> Vec<(f64, f64)> {
+    bays.iter()
+        .map(|bay| {
+            let half = bay.length / 2. + wall;
+            (bay.span - half, bay.span + half)
+        })
+        .collect()
+}
+
+/// The pocket of a servo bay, as its outline placed in the wing.
+#[derive(Debug, Clone)]
+pub struct Pocket {
+    /// Corners of the outline, in the plane through the middle of the bay.
+    pub outline: Vec<Point3>,
+    /// Length of the pocket along the span, centred on the outline.
+    pub length: f64,
+}
+
+impl Pocket {
+    /// The same pocket in the mirrored wing.
+    pub fn mirrored(&self) -> Pocket {
+        Pocket {
+            outline: wing::mirror_points(&self.outline),
+            length: self.length,
+        }
+    }
+
+    /// The solid filling the pocket.
+    pub fn solid(&self) -> Solid {
+        wing::extrude(&self.outline, self.length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_servo_bay() {
+        let bay: ServoBay = "200,0.35,23x12x10".parse().unwrap();
+        assert_eq!(
+            bay,
+            ServoBay {
+                span: 200.,
+                position: 0.35,
+                width: 23.,
+                length: 12.,
+                depth: 10.,
+                opening: Opening::Bottom,
+            }
+        );
+
+        let bay: ServoBay = "150, c/4, 23X12X8, top".parse().unwrap();
+        assert_eq!((bay.position, bay.depth), (0.25, 8.));
+        assert_eq!(bay.opening, Opening::Top);
+    }
+
+    #[test]
+    fn parse_servo_bay_errors() {
+        assert!("200,0.35".parse::<ServoBay>().is_err());
+        assert!("200,0.35,23x12".parse::<ServoBay>().is_err());
+        assert!("200,0.35,23x12x10,side".parse::<ServoBay>().is_err());
+        assert!("200,0.35,23x12xdeep".parse::<ServoBay>().is_err());
+    }
+}
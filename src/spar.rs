@@ -0,0 +1,264 @@
+use crate::airfoil;
+use crate::wing::{self, Side, Station};
+use kurbo::Point;
+use std::str::FromStr;
+use truck_modeling::{Point3, Solid, Vector3, Wire};
+
+/// Cross-section of a spar channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    /// A round tube of the given diameter.
+    Round(f64),
+    /// A rectangular bar, with the width along the chord and the height
+    /// through the wing.
+    Rectangular(f64, f64),
+}
+
+impl Shape {
+    /// Size of the section along its largest dimension.
+    pub fn size(&self) -> f64 {
+        match *self {
+            Shape::Round(diameter) => diameter,
+            Shape::Rectangular(width, height) => width.max(height),
+        }
+    }
+
+    /// The section centred on `center`, in a plane parallel to the root.
+    pub fn wire(&self, center: Point3) -> Wire {
+        let at = |x: f64, y: f64| center + Vector3::new(x, y, 0.);
+        match *self {
+            Shape::Round(diameter) => {
+                const SEGMENTS: usize = 24;
+                let r = diameter / 2.;
+                let corners: Vec<_> = (0..SEGMENTS)
+                    .map(|i| {
+                        let angle = std::f64::consts::TAU * i as f64 / SEGMENTS as f64;
+                        at(r * angle.cos(), r * angle.sin())
+                    })
+                    .collect();
+                wing::polygon(&corners)
+            }
+            Shape::Rectangular(width, height) => {
+                let (w, h) = (width / 2., height / 2.);
+                wing::polygon(&[at(w, -h), at(w, h), at(-w, h), at(-w, -h)])
+            }
+        }
+    }
+}
+
+impl FromStr for Shape {
+    type Err = String;
+
+    /// Parses `round:DIAMETER` or `rect:WIDTHxHEIGHT`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("expected round:DIAMETER or rect:WIDTHxHEIGHT, got {:?}", s);
+        let number = |f: &str| f.trim().parse::<f64>().map_err(|_| invalid());
+
+        let (kind, size) = s.split_once(':').ok_or_else(invalid)?;
+        match kind.trim().to_ascii_lowercase().as_str() {
+            "round" => Ok(Shape::Round(number(size)?)),
+            "rect" | "rectangular" => {
+                let (width, height) = size.split_once(['x', 'X']).ok_or_else(invalid)?;
+                Ok(Shape::Rectangular(number(width)?, number(height)?))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// A channel for a spar, cut through the wing along the span.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spar {
+    pub shape: Shape,
+    /// Chordwise position of the center of the spar, as a fraction of the
+    /// local chord.
+    pub position: f64,
+    /// Span at which the spar starts, defaulting to the root.
+    pub start: Option<f64>,
+    /// Span at which the spar ends, defaulting to the tip.
+    pub end: Option<f64>,
+    /// Run the spar in a straight line from its start to its end, instead of
+    /// following the sweep and dihedral of the wing.
+    pub straight: bool,
+}
+
+impl FromStr for Spar {
+    type Err = String;
+
+    /// Parses a spar given as `shape,position[,start[,end]][,straight]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields: Vec<&str> = s.split(',').map(str::trim).collect();
+        let straight = fields.last() == Some(&"straight");
+        if straight {
+            fields.pop();
+        }
+        if fields.len() < 2 || fields.len() > 4 {
+            return Err(format!(
+                "expected shape,position[,start[,end]][,straight], got {:?}",
+                s
+            ));
+        }
+
+        let number = |i: usize| -> Result<Option<f64>, String> {
+            fields
+                .get(i)
+                .map(|f| {
+                    f.parse()
+                        .map_err(|_| format!("invalid number {:?} in spar {:?}", f, s))
+                })
+                .transpose()
+        };
+
+        Ok(Spar {
+            shape: fields[0].parse()?,
+            position: wing::parse_chord_fraction(fields[1])?,
+            start: number(2)?,
+            end: number(3)?,
+            straight,
+        })
+    }
+}
+
+impl Spar {
+    /// The path of the spar along the wing made of `stations`.
+    pub fn path(&self, stations: &[Station], profiles: &[Vec<Point>], twist_axis: f64) -> Path {
+        let (root, tip) = (stations[0].span, stations[stations.len() - 1].span);
+        let start = self.start.unwrap_or(root).max(root);
+        let end = self.end.unwrap_or(tip).min(tip);
+        if start >= end {
+            panic!("spar must end further along the span than it starts");
+        }
+
+        let center = |span: f64| {
+            let (station, profile) = wing::section_at(stations, profiles, span);
+            let y = airfoil::mean_line_at(&profile, self.position);
+            let p =
+                station.transform(twist_axis) * Point3::new(self.position, y, 0.).to_homogeneous();
+            Point3::from_homogeneous(p)
+        };
+
+        let mut spans = vec![start];
+        if !self.straight {
+            spans.extend(
+                stations
+                    .iter()
+                    .map(|s| s.span)
+                    .filter(|&span| span > start && span < end),
+            );
+        }
+        spans.push(end);
+        Path {
+            centers: spans.into_iter().map(center).collect(),
+            open_start: start <= root,
+            open_end: end >= tip,
+        }
+    }
+
+    /// The solid filling the spar channel along `path`, carried on past its
+    /// open ends so the channel opens cleanly out of the end of the wing.
+    pub fn solid(&self, path: &Path) -> Solid {
+        // The end sections are moved out along the spar rather than added,
+        // so none lie in the plane of the root or tip face.
+        let overhang = |end: Point3, next: Point3| {
+            end + (end - next) * (self.shape.size() / (end.z - next.z).abs())
+        };
+        let mut centers = path.centers.clone();
+        let n = centers.len() - 1;
+        if path.open_start {
+            centers[0] = overhang(centers[0], centers[1]);
+        }
+        if path.open_end {
+            centers[n] = overhang(centers[n], centers[n - 1]);
+        }
+        let sections: Vec<Wire> = centers.iter().map(|&p| self.shape.wire(p)).collect();
+        wing::loft(&sections)
+    }
+}
+
+/// The line a spar channel follows through the wing.
+#[derive(Debug, Clone)]
+pub struct Path {
+    /// Centers of the spar's sections.
+    pub centers: Vec<Point3>,
+    /// Whether the spar runs out of the wing at the first center.
+    pub open_start: bool,
+    /// Whether the spar runs out of the wing at the last center.
+    pub open_end: bool,
+}
+
+impl Path {
+    /// The same path in the mirrored wing.
+    pub fn mirrored(&self) -> Path {
+        let mut centers = wing::mirror_points(&self.centers);
+        centers.reverse();
+        Path {
+            centers,
+            open_start: self.open_end,
+            open_end: self.open_start,
+        }
+    }
+
+    /// Joins a path which runs out of the root with its mirror image, for a
+    /// fused wing. The root center is shared by both halves.
+    pub fn fused(&self) -> Path {
+        let mut joined = self.mirrored();
+        joined.centers.extend(&self.centers[1..]);
+        joined.open_end = self.open_end;
+        joined
+    }
+}
+
+/// The channels for `spars` along their paths in one side of the wing, or
+/// both sides for `Side::Both`. Each side has channels along their own
+/// paths, with those running out of the root joined up when both are fused
+/// into one.
+pub fn channels(spars: &[(Spar, Path)], side: Side) -> Vec<Solid> {
+    spars
+        .iter()
+        .flat_map(|(spar, path)| match side {
+            Side::Left => vec![spar.solid(path)],
+            Side::Right => vec![spar.solid(&path.mirrored())],
+            Side::Both => match path.open_start {
+                true => vec![spar.solid(&path.fused())],
+                false => vec![spar.solid(path), spar.solid(&path.mirrored())],
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_spar() {
+        let spar: Spar = "round:6,c/4".parse().unwrap();
+        assert_eq!(
+            spar,
+            Spar {
+                shape: Shape::Round(6.),
+                position: 0.25,
+                start: None,
+                end: None,
+                straight: false,
+            }
+        );
+
+        let spar: Spar = "rect:10x3, 0.3, 20, 180, straight".parse().unwrap();
+        assert_eq!(spar.shape, Shape::Rectangular(10., 3.));
+        assert_eq!(
+            (spar.position, spar.start, spar.end),
+            (0.3, Some(20.), Some(180.))
+        );
+        assert!(spar.straight);
+    }
+
+    #[test]
+    fn parse_spar_errors() {
+        assert!("round:6".parse::<Spar>().is_err());
+        assert!("square:6,0.3".parse::<Spar>().is_err());
+        assert!("rect:10,0.3".parse::<Spar>().is_err());
+        assert!("round:6,0.3,1,2,3".parse::<Spar>().is_err());
+        assert!("round:6,middle".parse::<Spar>().is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use kurbo::{BezPath, PathEl, Point};
+
+/// Builds a smooth path through the given points, made of cubic Bézier
+/// segments that together form a C2 continuous interpolating spline.
+///
+/// The spline is parameterized by chord length with natural end conditions,
+/// so a sharp trailing edge stays sharp.
+pub fn smooth_path(points: &[Point]) -> BezPath {
+    let mut pts: Vec<Point> = Vec::with_capacity(points.len());
+    for &p in points {
+        if pts.last() != Some(&p) {
+            pts.push(p);
+        }
+    }
+
+    let mut path = BezPath::new();
+    path.push(PathEl::MoveTo(pts[0]));
+    if pts.len() < 3 {
+        for &p in &pts[1..] {
+            path.push(PathEl::LineTo(p));
+        }
+        return path;
+    }
+
+    let mut t = vec![0.];
+    for w in pts.windows(2) {
+        t.push(t.last().unwrap() + w[0].distance(w[1]));
+    }
+    let xs: Vec<f64> = pts.iter().map(|p| p.x).collect();
+    let ys: Vec<f64> = pts.iter().map(|p| p.y).collect();
+    let (mx, my) = (natural_spline(&t, &xs), natural_spline(&t, &ys));
+
+    // First derivative at either end of interval i.
+    let slope = |v: &[f64], m: &[f64], i: usize, end: bool| {
+        let h = t[i + 1] - t[i];
+        let d = (v[i + 1] - v[i]) / h;
+        if end {
+            d + h * (m[i] + 2. * m[i + 1]) / 6.
+        } else {
+            d - h * (2. * m[i] + m[i + 1]) / 6.
+        }
+    };
+
+    for i in 0..pts.len() - 1 {
+        let h = t[i + 1] - t[i];
+        let d0 = (slope(&xs, &mx, i, false), slope(&ys, &my, i, false));
+        let d1 = (slope(&xs, &mx, i, true), slope(&ys, &my, i, true));
+        path.push(PathEl::CurveTo(
+            Point::new(pts[i].x + h * d0.0 / 3., pts[i].y + h * d0.1 / 3.),
+            Point::new(pts[i + 1].x - h * d1.0 / 3., pts[i + 1].y - h * d1.1 / 3.),
+            pts[i + 1],
+        ));
+    }
+    path
+}
+
+/// Second derivatives of the natural cubic spline through the given points.
+pub fn natural_spline(x: &[f64], y: &[f64]) -> Vec<f64> {
+    let n = x.len();
+    let mut m = vec![0.; n];
+    let mut c = vec![0.; n];
+    // Forward sweep of the tridiagonal system, with m[0] = m[n-1] = 0.
+    for i in 1..n - 1 {
+        let (h0, h1) = (x[i] - x[i - 1], x[i + 1] - x[i]);
+        let rhs = 6. * ((y[i + 1] - y[i]) / h1 - (y[i] - y[i - 1]) / h0);
+        let diag = 2. * (h0 + h1) - h0 * c[i - 1];
+        c[i] = h1 / diag;
+        m[i] = (rhs - h0 * m[i - 1]) / diag;
+    }
+    for i in (1..n - 1).rev() {
+        m[i] -= c[i] * m[i + 1];
+    }
+    m
+}
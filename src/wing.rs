@@ -0,0 +1,800 @@
+use crate::airfoil;
+use kurbo::Point;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::ops::Range;
+use std::str::FromStr;
+use truck_meshalgo::tessellation::{MeshableShape, MeshedShape};
+use truck_modeling::{
+    builder, BoundingBox, Deg, InnerSpace, Matrix4, Point3, Shell, Solid, Vector3, Wire,
+};
+use truck_polymesh::PolygonMesh;
+
+/// A spanwise station of the wing, where an airfoil section is placed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Station {
+    /// Distance from the root, along Z.
+    pub span: f64,
+    /// Chord length.
+    pub chord: f64,
+    /// Position of the leading edge along X.
+    pub le_offset: f64,
+    /// Height of the leading edge along Y, from the dihedral of the panels
+    /// inboard of this station. See [`apply_dihedral`].
+    pub height: f64,
+    /// Geometric twist in degrees, positive nose up.
+    pub twist: f64,
+    /// Dihedral angle in degrees of the panel ending at this station.
+    pub dihedral: f64,
+    /// Angle in degrees the section is rolled about the X axis, tilting the
+    /// direction it faces from Z towards Y. Zero keeps it parallel to the root.
+    pub roll: f64,
+    /// Airfoil of this section, if different from the main airfoil.
+    pub airfoil: Option<String>,
+}
+
+impl FromStr for Station {
+    type Err = String;
+
+    /// Parses a station given as
+    /// `span,chord[,le_offset[,twist[,dihedral]]][,airfoil]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields: Vec<&str> = s.split(',').map(str::trim).collect();
+
+        // The airfoil is the only field which isn't a number.
+        let airfoil = match fields.last() {
+            Some(f) if fields.len() > 2 && f.parse::<f64>().is_err() => {
+                fields.pop().map(|f| f.to_string())
+            }
+            _ => None,
+        };
+        if fields.len() < 2 || fields.len() > 5 {
+            return Err(format!(
+                "expected span,chord[,le_offset[,twist[,dihedral]]][,airfoil], got {:?}",
+                s
+            ));
+        }
+
+        let number = |i: usize| -> Result<f64, String> {
+            match fields.get(i) {
+                Some(f) => f
+                    .parse()
+                    .map_err(|_| format!("invalid number {:?} in station {:?}", f, s)),
+                None => Ok(0.),
+            }
+        };
+
+        Ok(Station {
+            span: number(0)?,
+            chord: number(1)?,
+            le_offset: number(2)?,
+            height: 0.,
+            twist: number(3)?,
+            dihedral: number(4)?,
+            roll: 0.,
+            airfoil,
+        })
+    }
+}
+
+/// Sets the height of each station from the dihedral of the panels inboard of
+/// it. Sections stay parallel to the root, so the root face is undistorted.
+pub fn apply_dihedral(stations: &mut [Station]) {
+    for i in 1..stations.len() {
+        let (inboard, outboard) = (&stations[i - 1], &stations[i]);
+        let rise = (outboard.span - inboard.span) * outboard.dihedral.to_radians().tan();
+        stations[i].height = inboard.height + rise;
+    }
+}
+
+impl Station {
+    /// Transform which places a unit-chord section at this station, twisting
+    /// it about the given fraction of the chord.
+    pub fn transform(&self, twist_axis: f64) -> Matrix4 {
+        let pivot = Vector3::new(twist_axis * self.chord, 0., 0.);
+        Matrix4::from_translation(Vector3::new(self.le_offset, self.height, self.span))
+            * Matrix4::from_angle_x(Deg(-self.roll))
+            * Matrix4::from_translation(pivot)
+            * Matrix4::from_angle_z(Deg(-self.twist))
+            * Matrix4::from_translation(-pivot)
+            * Matrix4::from_scale(self.chord)
+    }
+
+    /// The station `distance` along the panel from this station to `next`,
+    /// and the fraction of the panel that is.
+    pub fn towards(&self, next: &Station, distance: f64) -> (Station, f64) {
+        let length = (next.span - self.span).hypot(next.height - self.height);
+        if distance >= length {
+            panic!(
+                "panel of length {} is too short to move {} along it",
+                length, distance
+            );
+        }
+        let t = distance / length;
+        (self.lerp(next, t), t)
+    }
+
+    /// Blends this station into `next`, from this station at `t = 0` to
+    /// `next` at `t = 1`.
+    pub fn lerp(&self, next: &Station, t: f64) -> Station {
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+        Station {
+            span: lerp(self.span, next.span),
+            chord: lerp(self.chord, next.chord),
+            le_offset: lerp(self.le_offset, next.le_offset),
+            height: lerp(self.height, next.height),
+            twist: lerp(self.twist, next.twist),
+            dihedral: next.dihedral,
+            roll: lerp(self.roll, next.roll),
+            airfoil: None,
+        }
+    }
+
+    /// A copy of this station moved `distance` along the direction it faces.
+    pub fn extended(&self, distance: f64) -> Station {
+        let roll = self.roll.to_radians();
+        Station {
+            span: self.span + distance * roll.cos(),
+            height: self.height + distance * roll.sin(),
+            airfoil: None,
+            ..self.clone()
+        }
+    }
+}
+
+/// Spanwise chord distribution between the root and tip chords.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Planform {
+    /// Straight taper from root to tip
+    Linear,
+    /// Elliptical chord distribution, ending at the tip chord
+    Elliptical,
+    /// Multi-taper approximation of an elliptical wing, with straight panels
+    Schuemann,
+}
+
+impl Planform {
+    /// Span fractions at which sections are placed to follow the planform,
+    /// with the chord at each of them.
+    pub fn chords(&self, root_chord: f64, tip_chord: f64, sections: usize) -> Vec<(f64, f64)> {
+        let elliptical = |eta: f64| {
+            let c = (1. - eta * eta).max(0.).sqrt();
+            (eta, tip_chord + (root_chord - tip_chord) * c)
+        };
+        match self {
+            Planform::Linear => vec![(0., root_chord), (1., tip_chord)],
+            Planform::Elliptical => {
+                // Clustered towards the tip, where the chord changes fastest.
+                let n = sections.max(2);
+                (0..=n)
+                    .map(|i| elliptical((std::f64::consts::FRAC_PI_2 * i as f64 / n as f64).sin()))
+                    .collect()
+            }
+            Planform::Schuemann => [0., 0.5, 0.75, 0.9, 1.]
+                .into_iter()
+                .map(elliptical)
+                .collect(),
+        }
+    }
+}
+
+/// Reads a chord distribution of `span chord` pairs, one per line, from the
+/// root to the tip.
+pub fn read_chord_table(path: &str) -> Vec<(f64, f64)> {
+    let reader = BufReader::new(File::open(path).expect("failed opening chord table"));
+    let table: Vec<(f64, f64)> = reader
+        .lines()
+        .map(|l| l.unwrap())
+        .filter(|l| !l.trim().is_empty() && !l.trim().starts_with('#'))
+        .map(|l| {
+            let numbers: Vec<f64> = l
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|f| !f.is_empty())
+                .map(|f| {
+                    f.parse()
+                        .unwrap_or_else(|_| panic!("invalid number in chord table: {:?}", l))
+                })
+                .collect();
+            if numbers.len() != 2 {
+                panic!("expected span,chord in chord table, got {:?}", l);
+            }
+            (numbers[0], numbers[1])
+        })
+        .collect();
+
+    if table.len() < 2 || table[0].0 != 0. {
+        panic!("chord table must start at the root (span 0) and have at least two entries");
+    }
+    if table.windows(2).any(|w| w[1].0 <= w[0].0) {
+        panic!("chord table must be in order of increasing span");
+    }
+    table
+}
+
+/// Places sections between a root and tip station at the given span
+/// fractions and chords, keeping the line at `reference` fraction of the chord
+/// straight. Twist and height are interpolated linearly.
+pub fn distribute(
+    root: &Station,
+    tip: &Station,
+    chords: &[(f64, f64)],
+    reference: f64,
+) -> Vec<Station> {
+    let (root_ref, tip_ref) = (
+        root.le_offset + reference * root.chord,
+        tip.le_offset + reference * tip.chord,
+    );
+    let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+    chords
+        .iter()
+        .map(|&(eta, chord)| Station {
+            span: lerp(root.span, tip.span, eta),
+            chord,
+            le_offset: lerp(root_ref, tip_ref, eta) - reference * chord,
+            height: lerp(root.height, tip.height, eta),
+            twist: lerp(root.twist, tip.twist, eta),
+            dihedral: tip.dihedral,
+            roll: tip.roll,
+            airfoil: None,
+        })
+        .collect()
+}
+
+/// Shape of the end of the wing beyond the tip section.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum TipCap {
+    /// A flat face across the tip section
+    Flat,
+    /// A rounded tip, as if the tip section was revolved about its camber line
+    Round,
+    /// A raked tip, with the leading edge swept back to meet the trailing edge
+    Sheared,
+    /// A Hoerner tip, with the lower surface curving up to a sharp upper edge
+    Hoerner,
+}
+
+impl TipCap {
+    /// Sections which extend the wing beyond the tip station to shape its end.
+    ///
+    /// The thickness or chord of the sections shrinks towards the end of the
+    /// cap, which is closed with a small flat face. `length` is the spanwise
+    /// length of the cap, defaulting to a length suited to the style.
+    pub fn sections(
+        &self,
+        tip: &Station,
+        profile: &[Point],
+        length: Option<f64>,
+    ) -> Vec<(Station, Vec<Point>)> {
+        const SECTIONS: usize = 8;
+        // The last section is left with this fraction of the tip thickness or chord.
+        const END: f64 = 0.1;
+
+        let thickness = airfoil::thickness(profile) * tip.chord;
+        let at = |distance: f64| tip.extended(distance);
+
+        match self {
+            TipCap::Flat => Vec::new(),
+            TipCap::Round | TipCap::Hoerner => {
+                // Round caps revolve about the camber line, so extend by half
+                // the thickness. Hoerner caps pivot about the upper surface,
+                // apart from the leading edge which stays round.
+                let (anchor, default_length): (fn(f64) -> f64, f64) = match self {
+                    TipCap::Round => (|_| 0.5, thickness / 2.),
+                    _ => (|x| 0.5 + 0.5 * (x / 0.2).clamp(0., 1.).sqrt(), thickness),
+                };
+                let length = length.unwrap_or(default_length);
+                let end = END.acos();
+                (1..=SECTIONS)
+                    .map(|i| {
+                        let theta = end * i as f64 / SECTIONS as f64;
+                        (
+                            at(length * theta.sin()),
+                            airfoil::scale_thickness(profile, theta.cos(), anchor),
+                        )
+                    })
+                    .collect()
+            }
+            TipCap::Sheared => {
+                let length = length.unwrap_or(tip.chord / 2.);
+                (1..=SECTIONS)
+                    .map(|i| {
+                        let t = i as f64 / SECTIONS as f64;
+                        // Ease in, so the leading edge is tangent at the tip.
+                        let chord = tip.chord * (1. - (1. - END) * t * t);
+                        let mut station = at(length * t);
+                        station.le_offset += tip.chord - chord;
+                        station.chord = chord;
+                        (station, profile.to_vec())
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// A winglet lofted off the wing tip, turning up through a circular
+/// transition into a straight tapered panel.
+#[derive(Debug, Clone)]
+pub struct Winglet {
+    /// Length of the straight panel, beyond the transition.
+    pub height: f64,
+    /// Cant angle in degrees from vertical, positive leaning outboard.
+    pub cant: f64,
+    /// Toe angle in degrees, positive with the leading edge outboard.
+    pub toe: f64,
+    /// Leading edge sweep angle in degrees.
+    pub sweep: f64,
+    /// Ratio of the winglet tip chord to the wing tip chord.
+    pub taper: f64,
+    /// Radius of the transition from the wing to the winglet, defaulting to
+    /// twice the thickness of the tip section.
+    pub radius: Option<f64>,
+}
+
+impl Winglet {
+    /// Sections which continue the wing from its tip station into the
+    /// winglet. The airfoil blends from `tip_profile` to `profile` through
+    /// the transition.
+    pub fn sections(
+        &self,
+        tip: &Station,
+        tip_profile: &[Point],
+        profile: &[Point],
+    ) -> Vec<(Station, Vec<Point>)> {
+        const TRANSITION_SECTIONS: usize = 6;
+
+        // The upper surface is on the inside of the transition, so it must
+        // not reach the center of the bend.
+        let upper = tip_profile.iter().map(|p| p.y).fold(0., f64::max) * tip.chord;
+        let radius = self
+            .radius
+            .unwrap_or(2. * airfoil::thickness(tip_profile) * tip.chord);
+        if radius <= upper {
+            panic!(
+                "winglet radius must be larger than the tip section's upper surface height of {}",
+                upper
+            );
+        }
+
+        let turn = (90. - self.cant).to_radians();
+        let length = radius * turn + self.height;
+        let station = |distance: f64, angle: f64, height: f64, span: f64, blend: f64| Station {
+            span,
+            chord: tip.chord * (1. + (self.taper - 1.) * distance / length),
+            le_offset: tip.le_offset + distance * self.sweep.to_radians().tan(),
+            height,
+            twist: tip.twist + (-self.toe - tip.twist) * blend,
+            dihedral: tip.dihedral,
+            roll: tip.roll + angle.to_degrees(),
+            airfoil: None,
+        };
+
+        let mut sections: Vec<(Station, Vec<Point>)> = (1..=TRANSITION_SECTIONS)
+            .map(|i| {
+                let blend = i as f64 / TRANSITION_SECTIONS as f64;
+                let angle = turn * blend;
+                let s = station(
+                    radius * angle,
+                    angle,
+                    tip.height + radius * (1. - angle.cos()),
+                    tip.span + radius * angle.sin(),
+                    blend,
+                );
+                (s, airfoil::lerp(tip_profile, profile, blend))
+            })
+            .collect();
+
+        let end = &sections.last().unwrap().0;
+        let winglet_tip = station(
+            length,
+            turn,
+            end.height + self.height * turn.sin(),
+            end.span + self.height * turn.cos(),
+            1.,
+        );
+        sections.push((winglet_tip, profile.to_vec()));
+        sections
+    }
+}
+
+/// Which half of the wing to generate.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Side {
+    /// The semi-wing as defined, extending along +Z
+    Left,
+    /// The semi-wing mirrored across the root plane
+    Right,
+    /// Both semi-wings
+    Both,
+}
+
+impl Side {
+    /// The things made for the `left` semi-wing, the `right` one, or both.
+    pub fn select<T>(
+        self,
+        left: impl IntoIterator<Item = T>,
+        right: impl IntoIterator<Item = T>,
+    ) -> Vec<T> {
+        match self {
+            Side::Left => left.into_iter().collect(),
+            Side::Right => right.into_iter().collect(),
+            Side::Both => left.into_iter().chain(right).collect(),
+        }
+    }
+}
+
+/// Mirrors stations across the root plane, keeping them in order of
+/// increasing span.
+pub fn mirrored(stations: &[Station]) -> Vec<Station> {
+    stations
+        .iter()
+        .rev()
+        .map(|s| Station {
+            span: -s.span,
+            roll: -s.roll,
+            ..s.clone()
+        })
+        .collect()
+}
+
+/// The station and airfoil at `span`, blended from those either side of it.
+pub fn section_at(
+    stations: &[Station],
+    profiles: &[Vec<Point>],
+    span: f64,
+) -> (Station, Vec<Point>) {
+    let i = stations[..stations.len() - 1]
+        .iter()
+        .rposition(|s| s.span <= span)
+        .unwrap_or(0);
+    let (a, b) = (&stations[i], &stations[i + 1]);
+    let t = ((span - a.span) / (b.span - a.span)).clamp(0., 1.);
+    (
+        a.lerp(b, t),
+        airfoil::lerp(&profiles[i], &profiles[i + 1], t),
+    )
+}
+
+/// Parses a chord reference line: `le`, `c/4`, `te` or a fraction of the chord.
+pub fn parse_chord_fraction(s: &str) -> Result<f64, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "le" => Ok(0.),
+        "c/4" | "qc" => Ok(0.25),
+        "c/2" => Ok(0.5),
+        "te" => Ok(1.),
+        other => other
+            .parse()
+            .map_err(|_| format!("expected le, c/4, te or a chord fraction, got {:?}", s)),
+    }
+}
+
+/// Leading edge offset of a tip section, such that the line at `reference`
+/// fraction of the chord is swept back by `angle` degrees.
+pub fn swept_le_offset(
+    angle: f64,
+    reference: f64,
+    span: f64,
+    root_chord: f64,
+    tip_chord: f64,
+) -> f64 {
+    span * angle.to_radians().tan() + reference * (root_chord - tip_chord)
+}
+
+/// Reads stations from a file, one per line in the same format as `--station`.
+/// Empty lines and lines starting with `#` are ignored.
+pub fn read_stations(path: &str) -> Vec<Station> {
+    let reader = BufReader::new(File::open(path).expect("failed opening stations file"));
+    reader
+        .lines()
+        .map(|l| l.unwrap())
+        .filter(|l| !l.trim().is_empty() && !l.trim().starts_with('#'))
+        .map(|l| l.parse().unwrap_or_else(|e: String| panic!("{}", e)))
+        .collect()
+}
+
+/// Lofts the given section wires panel by panel into a single closed solid.
+///
+/// Neighbouring panels share the section wire between them, so the result is
+/// one closed shell capped at the first and last sections.
+pub fn loft(sections: &[Wire]) -> Solid {
+    if sections.len() < 2 {
+        panic!("at least two sections are needed to loft a wing");
+    }
+
+    let mut shell: Shell = Shell::new();
+    for pair in sections.windows(2) {
+        shell.extend(builder::try_wire_homotopy(&pair[0], &pair[1]).unwrap());
+    }
+
+    // Inverted bc opposite faces must have opposite normals
+    shell.push(
+        builder::try_attach_plane(&[sections[0].clone()])
+            .unwrap()
+            .inverse(),
+    );
+    shell.push(builder::try_attach_plane(&[sections.last().unwrap().clone()]).unwrap());
+
+    Solid::new(vec![shell])
+}
+
+/// Hollows out `solid`, leaving the space taken by `cavity` empty inside it.
+/// The result is a solid with two shells, the outer skin and the inside
+/// surface facing inwards.
+pub fn hollow(solid: Solid, mut cavity: Solid) -> Solid {
+    cavity.not();
+    let shells = solid.into_boundaries().into_iter();
+    Solid::new(shells.chain(cavity.into_boundaries()).collect())
+}
+
+/// The sections of the inside of a hollow wing, taken from those in `range`
+/// of the sections at `stations` with the ends inset by the skin. The inside
+/// is split in two around each of the `solid` spans, such as ribs, leaving
+/// them solid between the cavities, which are each given as their stations
+/// and the profiles of the inside of the skin there.
+pub fn cavities(
+    stations: &[Station],
+    profiles: &[Vec<Point>],
+    range: Range<usize>,
+    solid: &[(f64, f64)],
+    wall: f64,
+) -> Vec<(Vec<Station>, Vec<Vec<Point>>)> {
+    let (mut stations, mut profiles) = (stations[range.clone()].to_vec(), profiles[range].to_vec());
+    let n = stations.len() - 1;
+    if n < 1 {
+        panic!("at least two sections are needed to hollow a wing");
+    }
+    for (end, next) in [(0, 1), (n, n - 1)] {
+        let (station, t) = stations[end].towards(&stations[next], wall);
+        profiles[end] = airfoil::lerp(&profiles[end], &profiles[next], t);
+        stations[end] = station;
+    }
+
+    let mut cuts: Vec<f64> = solid.iter().flat_map(|&(from, to)| [from, to]).collect();
+    cuts.sort_by(f64::total_cmp);
+    let mut cuts = cuts.into_iter().enumerate().peekable();
+    let mut pieces = vec![(vec![stations[0].clone()], vec![profiles[0].clone()])];
+    let mut inside = false;
+    for i in 1..stations.len() {
+        let (a, b) = (&stations[i - 1], &stations[i]);
+        while let Some((j, cut)) = cuts.next_if(|&(_, cut)| cut < b.span) {
+            let t = (cut - a.span) / (b.span - a.span);
+            let section = (
+                a.lerp(b, t),
+                airfoil::lerp(&profiles[i - 1], &profiles[i], t),
+            );
+            // Cuts alternate between the ends of the solid spans.
+            if j % 2 == 1 {
+                pieces.push((vec![], vec![]));
+            }
+            let piece = pieces.last_mut().unwrap();
+            piece.0.push(section.0);
+            piece.1.push(section.1);
+            inside = j % 2 == 0;
+        }
+        // Sections inside a solid span are left out of the cavities.
+        if !inside {
+            let piece = pieces.last_mut().unwrap();
+            piece.0.push(b.clone());
+            piece.1.push(profiles[i].clone());
+        }
+    }
+
+    pieces
+        .into_iter()
+        .map(|(stations, profiles)| {
+            let profiles = stations
+                .iter()
+                .zip(&profiles)
+                .map(|(station, points)| airfoil::offset(points, wall / station.chord))
+                .collect();
+            (stations, profiles)
+        })
+        .collect()
+}
+
+/// Joins `solids` lying clear of each other into one, so they can be cut
+/// from another solid together.
+pub fn join(solids: impl IntoIterator<Item = Solid>) -> Solid {
+    Solid::new(
+        solids
+            .into_iter()
+            .flat_map(Solid::into_boundaries)
+            .collect(),
+    )
+}
+
+/// Cuts `tool` out of `solid`.
+pub fn subtract(solid: &Solid, tool: &Solid) -> Solid {
+    boolean(solid, tool, false)
+}
+
+/// The part of `solid` inside `tool`.
+pub fn intersect(solid: &Solid, tool: &Solid) -> Solid {
+    boolean(solid, tool, true)
+}
+
+/// Keeps the part of `solid` inside `tool`, or outside it if not `inside`.
+fn boolean(solid: &Solid, tool: &Solid, inside: bool) -> Solid {
+    // Boolean operations only work on shells whose surfaces cross the tool,
+    // so the other shells are set aside, and kept whole if they're on the
+    // side of the tool being kept.
+    let tool_mesh = tool.triangulation(BOOLEAN_TOLERANCE).to_polygon();
+    let tool_box = tool_mesh.bounding_box();
+    let (crossing, apart): (Vec<Shell>, Vec<Shell>) =
+        solid.boundaries().iter().cloned().partition(|shell| {
+            // Only faces around the tool are meshed to look for crossings.
+            let near: Shell = shell
+                .face_iter()
+                .filter(|face| {
+                    let mut bbox: BoundingBox<Point3> =
+                        face.vertex_iter().map(|v| v.get_point()).collect();
+                    let margin = bbox.diameter() * 0.1 + BOOLEAN_TOLERANCE;
+                    bbox.push(&(*bbox.max() + Vector3::new(margin, margin, margin)));
+                    bbox.push(&(*bbox.min() - Vector3::new(margin, margin, margin)));
+                    overlap(&bbox, &tool_box)
+                })
+                .cloned()
+                .collect();
+            let mesh = near.triangulation(BOOLEAN_TOLERANCE).to_polygon();
+            crosses(&mesh, &tool_mesh) || crosses(&tool_mesh, &mesh)
+        });
+    let mut shells: Vec<Shell> = apart
+        .into_iter()
+        .filter(|shell| {
+            let point = shell.vertex_iter().next().unwrap().get_point();
+            contains(&tool_mesh, point) == inside
+        })
+        .collect();
+
+    let mut tool = tool.clone();
+    if !inside {
+        tool.not();
+    }
+    if crossing.is_empty() {
+        // The tool is either entirely inside the solid or entirely apart.
+        let solid_mesh = solid.triangulation(BOOLEAN_TOLERANCE).to_polygon();
+        if contains(&solid_mesh, tool_mesh.positions()[0]) {
+            shells.extend(tool.into_boundaries());
+        }
+        return Solid::new(shells);
+    }
+
+    let cut = truck_shapeops::and(&Solid::new(crossing), &tool, BOOLEAN_TOLERANCE)
+        .expect("unable to cut the wing, try moving the cut slightly");
+    shells.extend(cut.into_boundaries());
+    Solid::new(shells)
+}
+
+/// Mirrors `points` across the root plane.
+pub fn mirror_points(points: &[Point3]) -> Vec<Point3> {
+    points.iter().map(|p| Point3::new(p.x, p.y, -p.z)).collect()
+}
+
+/// The prism through the polygon `outline`, which lies across the span,
+/// reaching `depth / 2` either side of it along Z.
+pub fn extrude(outline: &[Point3], depth: f64) -> Solid {
+    let wire = |offset: f64| {
+        let outline: Vec<_> = outline
+            .iter()
+            .map(|p| p + Vector3::new(0., 0., offset))
+            .collect();
+        polygon(&outline)
+    };
+    loft(&[wire(-depth / 2.), wire(depth / 2.)])
+}
+
+/// A closed wire through the corners of a polygon.
+pub fn polygon(corners: &[Point3]) -> Wire {
+    let vertices: Vec<_> = corners.iter().map(|&p| builder::vertex(p)).collect();
+    let n = vertices.len();
+    (0..n)
+        .map(|i| builder::line(&vertices[i], &vertices[(i + 1) % n]))
+        .collect()
+}
+
+/// Tolerance of the intersections between surfaces in boolean operations.
+const BOOLEAN_TOLERANCE: f64 = 0.05;
+
+/// Whether the boxes `a` and `b` overlap. The intersection of boxes apart
+/// along Y or Z isn't empty as far as `BoundingBox::is_empty` goes, which
+/// only looks along X.
+fn overlap(a: &BoundingBox<Point3>, b: &BoundingBox<Point3>) -> bool {
+    let (a0, a1, b0, b1) = (a.min(), a.max(), b.min(), b.max());
+    (0..3).all(|i| a0[i] <= b1[i] && b0[i] <= a1[i])
+}
+
+/// Triangles of a mesh, as their corner positions.
+fn triangles(mesh: &PolygonMesh) -> impl Iterator<Item = [Point3; 3]> + '_ {
+    let positions = mesh.positions();
+    mesh.faces()
+        .triangle_iter()
+        .map(move |tri| tri.map(|v| positions[v.pos]))
+}
+
+/// Distance along `dir` from `origin` to where it hits a triangle, as a
+/// multiple of the length of `dir`.
+fn hit(tri: [Point3; 3], origin: Point3, dir: Vector3) -> Option<f64> {
+    let [a, b, c] = tri;
+    let (e1, e2) = (b - a, c - a);
+    let p = dir.cross(e2);
+    let det = e1.dot(p);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let s = origin - a;
+    let u = s.dot(p) / det;
+    let q = s.cross(e1);
+    let v = dir.dot(q) / det;
+    let t = e2.dot(q) / det;
+    (u >= 0. && v >= 0. && u + v <= 1. && t > 0.).then_some(t)
+}
+
+/// Whether `point` is inside the closed `mesh`, by counting the triangles
+/// crossed by a ray from it.
+fn contains(mesh: &PolygonMesh, point: Point3) -> bool {
+    // Skewed so it's unlikely to pass exactly through an edge.
+    let dir = Vector3::new(1., 0.31, 0.17);
+    let crossings = triangles(mesh)
+        .filter(|&tri| hit(tri, point, dir).is_some())
+        .count();
+    crossings % 2 == 1
+}
+
+/// Whether any edge of mesh `a` passes through a triangle of mesh `b`.
+fn crosses(a: &PolygonMesh, b: &PolygonMesh) -> bool {
+    // Only triangles around the other mesh can cross it.
+    let (a_box, b_box) = (a.bounding_box(), b.bounding_box());
+    let near = |tri: &[Point3; 3], bbox: &BoundingBox<Point3>| overlap(&tri.iter().collect(), bbox);
+    let b: Vec<_> = triangles(b)
+        .filter(|tri| near(tri, &a_box))
+        .map(|tri| (tri, tri.iter().collect::<BoundingBox<Point3>>()))
+        .collect();
+    triangles(a).filter(|tri| near(tri, &b_box)).any(|tri| {
+        (0..3).any(|i| {
+            let (p, q) = (tri[i], tri[(i + 1) % 3]);
+            // Each edge is only tested against the triangles it could reach.
+            let edge: BoundingBox<Point3> = [p, q].into_iter().collect();
+            b.iter()
+                .filter(|(_, bbox)| overlap(&edge, bbox))
+                .any(|&(t, _)| hit(t, p, q - p).is_some_and(|t| t <= 1.))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_station() {
+        let station: Station = "200, 80, 15, -2, 5".parse().unwrap();
+        assert_eq!(
+            (station.span, station.chord, station.le_offset),
+            (200., 80., 15.)
+        );
+        assert_eq!((station.twist, station.dihedral), (-2., 5.));
+        assert_eq!(station.airfoil, None);
+
+        let station: Station = "0,100".parse().unwrap();
+        assert_eq!((station.span, station.chord, station.twist), (0., 100., 0.));
+    }
+
+    #[test]
+    fn parse_station_airfoil() {
+        // The airfoil may come after any of the numbers, and may be a path
+        // with spaces in it.
+        let station: Station = "200,80,naca2412".parse().unwrap();
+        assert_eq!(station.airfoil.as_deref(), Some("naca2412"));
+        let station: Station = "200,80,15,-2,my airfoils/tip.dat".parse().unwrap();
+        assert_eq!(station.twist, -2.);
+        assert_eq!(station.airfoil.as_deref(), Some("my airfoils/tip.dat"));
+    }
+
+    #[test]
+    fn parse_station_errors() {
+        assert!("200".parse::<Station>().is_err());
+        assert!("200,naca2412".parse::<Station>().is_err());
+        assert!("200,80,1,2,3,4".parse::<Station>().is_err());
+        assert!("200,eighty".parse::<Station>().is_err());
+    }
+}